@@ -0,0 +1,24 @@
+# Sourced by the scripts that talk to the contest server.  Sets
+# API_TOKEN from DAVAR_API_TOKEN, or else from a file only we can
+# read, the same way the solver does.
+
+CREDENTIALS=${DAVAR_CREDENTIALS:-$HOME/.davar-token}
+if [ -n "$DAVAR_API_TOKEN" ]; then
+    API_TOKEN=$DAVAR_API_TOKEN
+elif [ -f "$CREDENTIALS" ]; then
+    if [ -n "$(find "$CREDENTIALS" -perm /077)" ]; then
+        echo "$CREDENTIALS must not be readable by others (chmod 600)" >&2
+        exit 1
+    fi
+    API_TOKEN=$(cat "$CREDENTIALS")
+else
+    echo "no API token: set DAVAR_API_TOKEN or put the token in $CREDENTIALS (chmod 600)" >&2
+    exit 1
+fi
+
+# The token as a curl config, for `curl_config | curl --config - ...`.
+# Anyone on the machine can read a command line with ps, so the token
+# must never be on one; printf is built into the shell.
+curl_config() {
+    printf 'user = ":%s"\n' "$(printf '%s' "$API_TOKEN" | sed 's/[\\"]/\\&/g')"
+}
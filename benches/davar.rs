@@ -0,0 +1,108 @@
+//! How fast the simulator and solvers are, on problems small and
+//! large, playing games we saved in `solutions/`.  `make bench`
+//! compares against the results saved by the last `make bench-save`,
+//! and criterion keeps its reports in target/criterion.
+
+#[macro_use]
+extern crate criterion;
+extern crate davar;
+
+use criterion::{black_box, BenchmarkId, Criterion, Throughput};
+use davar::opts::DavarOptions;
+use davar::in_out::load_solutions;
+use davar::problems::Problems;
+use davar::solver;
+use davar::*;
+
+/// The best games we have saved for a small problem, a filled in one,
+/// a large one and one with many pieces.
+const GAMES: [&str; 4] = ["solutions/0-0-7145.json", "solutions/1-0-4118.json",
+                          "solutions/14-0-20066.json", "solutions/24-18-4172.json"];
+
+const PHRASES: [&str; 3] = ["ei!", "ia! ia!", "yogsothoth"];
+
+fn problems() -> Problems {
+    Problems::from_dir("problems").expect("Couldn't read the problems.")
+}
+
+fn games() -> Vec<Solution> {
+    GAMES.iter().map(|f| load_solutions(f).expect("Couldn't read a saved game.").remove(0)).collect()
+}
+
+/// The same options every time, so every run plays the same games.
+fn opts() -> DavarOptions {
+    DavarOptions {
+        phrases_of_power: PHRASES.iter().map(|p| p.to_string()).collect(),
+        rng_seed: Some(1),
+        ..DavarOptions::default()
+    }
+}
+
+/// Replaying a whole game, a command at a time.
+fn apply(c: &mut Criterion) {
+    let ps = problems();
+    let mut group = c.benchmark_group("apply");
+    for sol in games() {
+        let s = ps.initial_state(sol.problem_id, sol.seed).unwrap();
+        let cmds = string_to_commands(&sol.solution);
+        group.throughput(Throughput::Elements(cmds.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(sol.problem_id), &cmds, |b, cmds| {
+            b.iter(|| {
+                let mut s = s.clone();
+                for &cmd in cmds {
+                    s = s.apply(cmd);
+                }
+                s
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Copying a state halfway through a game, as the solvers do all the
+/// time.
+fn clone(c: &mut Criterion) {
+    let ps = problems();
+    let mut group = c.benchmark_group("clone");
+    for sol in games() {
+        let cmds = string_to_commands(&sol.solution);
+        let s = ps.initial_state(sol.problem_id, sol.seed).unwrap().apply_sequence(&cmds[.. cmds.len()/2]);
+        group.bench_with_input(BenchmarkId::from_parameter(sol.problem_id), &s, |b, s| {
+            b.iter(|| black_box(s).clone())
+        });
+    }
+    group.finish();
+}
+
+fn resting_positions(c: &mut Criterion) {
+    let ps = problems();
+    let mut group = c.benchmark_group("resting_positions");
+    for sol in games() {
+        let s = ps.initial_state(sol.problem_id, sol.seed).unwrap();
+        group.bench_with_input(BenchmarkId::from_parameter(sol.problem_id), &s, |b, s| {
+            b.iter(|| solver::enumerate_resting_positions(black_box(s)))
+        });
+    }
+    group.finish();
+}
+
+/// Whole games, with the quickest solver everywhere and a thorough
+/// one where it finishes in a second or so.
+fn solves(c: &mut Criterion) {
+    let ps = problems();
+    let mut group = c.benchmark_group("solve");
+    group.sample_size(10);
+    let runs = games().iter().map(|sol| ("alldown", sol.problem_id))
+        .chain([("bottomupdfs", 1), ("bottomupdfs", 13)].iter().cloned())
+        .collect::<Vec<_>>();
+    for (name, id) in runs {
+        let input = ps.get(id).unwrap();
+        let s = ps.initial_state(id, input.source_seeds[0]).unwrap();
+        let solver = solver::name_to_solver(name).expect("No such solver.");
+        group.bench_function(BenchmarkId::new(name, id), |b| b.iter(|| solver.solve(&s, input, &opts())));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, apply, clone, resting_positions, solves);
+criterion_main!(benches);
@@ -12,6 +12,7 @@ for phrase in phrases:
 cmd = ["./target/release/solve-davar"] + phrase_args + sys.argv[1:]
 
 
-print("Running with:" + " ".join(cmd))
+# On stderr, so that whatever we are run for gets only the output.
+sys.stderr.write("Running with:" + " ".join(cmd) + "\n")
 run = subprocess.Popen(cmd)
 run.communicate()
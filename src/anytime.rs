@@ -0,0 +1,79 @@
+//! The best solution found so far for every (problem, seed).
+//!
+//! Solvers publish snapshots here whenever they improve, so that
+//! when the deadline passes (or we get SIGINT/SIGTERM) `main` can
+//! print whatever is best right away, without waiting for solver
+//! threads that are still in the middle of a game.
+
+use super::*;
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+pub struct BestStore {
+    best: Mutex<BTreeMap<(i32, i32), (Solution, Score)>>,
+}
+
+static GLOBAL: BestStore = BestStore::new();
+static STOP: AtomicBool = AtomicBool::new(false);
+
+/// The store shared by every solver thread.
+pub fn global() -> &'static BestStore {
+    &GLOBAL
+}
+
+/// Ask for the run to end early, e.g. from a signal handler.
+pub fn request_stop() {
+    STOP.store(true, Ordering::SeqCst);
+}
+
+pub fn stop_requested() -> bool {
+    STOP.load(Ordering::SeqCst)
+}
+
+impl Default for BestStore {
+    fn default() -> BestStore {
+        BestStore::new()
+    }
+}
+
+impl BestStore {
+    pub const fn new() -> BestStore {
+        BestStore { best: Mutex::new(BTreeMap::new()) }
+    }
+    /// Record `sol` if it beats what we have for its problem and
+    /// seed.  Returns true if it was an improvement.
+    pub fn publish(&self, sol: Solution, score: Score) -> bool {
+        let mut best = self.best.lock().unwrap();
+        let key = (sol.problem_id, sol.seed);
+        let better = match best.get(&key) {
+            Some(&(_, old)) => score > old,
+            None => true,
+        };
+        if better {
+            best.insert(key, (sol, score));
+        }
+        better
+    }
+    pub fn best_score(&self, problem_id: i32, seed: i32) -> Option<Score> {
+        self.best.lock().unwrap().get(&(problem_id, seed)).map(|x| x.1)
+    }
+    /// The best solutions so far, ordered by problem and seed.
+    pub fn snapshot(&self) -> Vec<(Solution, Score)> {
+        self.best.lock().unwrap().values().cloned().collect()
+    }
+}
+
+#[test]
+fn keeps_the_best() {
+    let store = BestStore::new();
+    let sol = |s: &str| Solution { problem_id: 3, seed: 7, tag: None, solution: s.into() };
+    assert!(store.publish(sol("a"), 10));
+    assert!(!store.publish(sol("b"), 5));
+    assert!(!store.publish(sol("c"), 10));
+    assert!(store.publish(sol("d"), 12));
+    assert_eq!(store.best_score(3, 7), Some(12));
+    assert_eq!(store.best_score(3, 8), None);
+    assert_eq!(store.snapshot()[0].0.solution, "d");
+}
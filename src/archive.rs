@@ -0,0 +1,307 @@
+//! A local archive of every solution we have found.
+//!
+//! Each solution is appended to `solutions.jsonl` in the archive
+//! directory together with how it was made and its scores, as found
+//! by replaying it in the simulator.  `index.json` holds the best
+//! entry for each (problem, seed), and can be exported as a single
+//! submission.
+
+use super::*;
+
+use serde_json;
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_DIR: &str = "archive";
+
+#[derive(Debug, Eq, PartialEq, Clone, Hash, Serialize, Deserialize)]
+pub struct Entry {
+    pub problem_id: i32,
+    pub seed: i32,
+    pub solution: String,
+    pub tag: Option<String>,
+    pub solver: String,
+    pub options: Vec<String>,
+    pub phrases: Vec<String>,
+    pub timestamp: i64,
+    /// The score the solver reported.
+    pub claimed_score: Score,
+    pub move_score: Score,
+    pub power_score: Score,
+    /// `move_score + power_score`, which is what we rank by.
+    pub total_score: Score,
+}
+
+impl Entry {
+    /// Make an entry for `sol`, scoring it by replaying it from
+    /// `initial`, the starting state for its problem and seed.
+    pub fn new(sol: &Solution, claimed_score: Score, solver: &str, options: &[String],
+               phrases: &[String], initial: &State) -> Entry {
+        let mut e = Entry {
+            problem_id: sol.problem_id,
+            seed: sol.seed,
+            solution: sol.solution.clone(),
+            tag: sol.tag.clone(),
+            solver: solver.into(),
+            options: options.to_vec(),
+            phrases: phrases.to_vec(),
+            timestamp: time::get_time().sec,
+            claimed_score,
+            move_score: 0,
+            power_score: 0,
+            total_score: 0,
+        };
+        e.rescore(initial);
+        e
+    }
+    /// Recompute the scores by replaying the solution from `initial`.
+    /// A solution that ends in an error scores nothing at all.
+    pub fn rescore(&mut self, initial: &State) -> simulate::Ending {
+        let (s, ending) = simulate::replay(&string_to_commands(&self.solution), initial);
+        self.move_score = s.score;
+        self.power_score = match ending {
+            simulate::Ending::Clean => simulate::score_pop(&self.solution, &self.phrases),
+            _ => 0,
+        };
+        self.total_score = self.move_score + self.power_score;
+        ending
+    }
+    pub fn to_solution(&self) -> Solution {
+        Solution {
+            problem_id: self.problem_id,
+            seed: self.seed,
+            tag: self.tag.clone(),
+            solution: self.solution.clone(),
+        }
+    }
+}
+
+/// What `Archive::verify` found for one entry.
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct Verification {
+    pub entry: Entry,
+    /// The total score the archive had before verifying.
+    pub old_total: Score,
+    pub ending: simulate::Ending,
+}
+
+impl Verification {
+    /// Does anything about this entry need a human to look at it?
+    pub fn is_suspect(&self) -> bool {
+        self.ending != simulate::Ending::Clean
+            || self.entry.total_score != self.old_total
+            || self.entry.total_score != self.entry.claimed_score
+    }
+}
+
+pub struct Archive {
+    dir: PathBuf,
+    best: BTreeMap<(i32, i32), Entry>,
+}
+
+impl Archive {
+    /// Open the archive in `dir`, creating it if need be.
+    pub fn open<P: AsRef<Path>>(dir: P) -> io::Result<Archive> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let mut a = Archive { dir, best: BTreeMap::new() };
+        if a.index_path().exists() {
+            let mut f = File::open(a.index_path())?;
+            let mut contents = String::new();
+            io::Read::read_to_string(&mut f, &mut contents)?;
+            let entries: Vec<Entry> = match serde_json::from_str(&contents) {
+                Ok(e) => e,
+                Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e.to_string())),
+            };
+            for e in entries {
+                a.best.insert((e.problem_id, e.seed), e);
+            }
+        } else {
+            a.rebuild_index()?;
+        }
+        Ok(a)
+    }
+    fn entries_path(&self) -> PathBuf {
+        self.dir.join("solutions.jsonl")
+    }
+    fn index_path(&self) -> PathBuf {
+        self.dir.join("index.json")
+    }
+
+    /// Store `e`, and make it the best for its seed if it is.
+    pub fn add(&mut self, e: Entry) -> io::Result<()> {
+        let mut f = OpenOptions::new().create(true).append(true).open(self.entries_path())?;
+        writeln!(f, "{}", serde_json::to_string(&e).unwrap())?;
+        if self.consider(e) {
+            self.write_index()?;
+        }
+        Ok(())
+    }
+    /// Update the index with `e`, returning true if it is the new best.
+    fn consider(&mut self, e: Entry) -> bool {
+        let key = (e.problem_id, e.seed);
+        let better = match self.best.get(&key) {
+            Some(old) => e.total_score > old.total_score,
+            None => true,
+        };
+        if better {
+            self.best.insert(key, e);
+        }
+        better
+    }
+
+    /// Every entry ever stored, oldest first.
+    pub fn entries(&self) -> io::Result<Vec<Entry>> {
+        let mut out = Vec::new();
+        if !self.entries_path().exists() {
+            return Ok(out);
+        }
+        let f = BufReader::new(File::open(self.entries_path())?);
+        for line in f.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let e: Entry = match serde_json::from_str(&line) {
+                Ok(e) => e,
+                Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e.to_string())),
+            };
+            e.to_solution().check()?;
+            out.push(e);
+        }
+        Ok(out)
+    }
+    /// Replace every stored entry, e.g. after rescoring them, and
+    /// rebuild the index from them.
+    pub fn replace_entries(&mut self, entries: &[Entry]) -> io::Result<()> {
+        replace_file(self.entries_path(), |f| {
+            for e in entries {
+                writeln!(f, "{}", serde_json::to_string(e).unwrap())?;
+            }
+            Ok(())
+        })?;
+        self.rebuild_index()
+    }
+    /// Replay every entry with the current simulator, store the new
+    /// scores and rebuild the index from them.  `initial` gives the
+    /// starting state for a problem and seed, if we know it; entries
+    /// we cannot replay are left as they are.
+    pub fn verify<F>(&mut self, mut initial: F) -> io::Result<Vec<Verification>>
+        where F: FnMut(i32, i32) -> Option<State>
+    {
+        let mut entries = self.entries()?;
+        let mut out = Vec::new();
+        for e in entries.iter_mut() {
+            if let Some(s) = initial(e.problem_id, e.seed) {
+                let old_total = e.total_score;
+                let ending = e.rescore(&s);
+                out.push(Verification { entry: e.clone(), old_total, ending });
+            }
+        }
+        self.replace_entries(&entries)?;
+        Ok(out)
+    }
+    /// Recompute the best entry for each seed from all the entries.
+    pub fn rebuild_index(&mut self) -> io::Result<()> {
+        self.best.clear();
+        for e in self.entries()? {
+            self.consider(e);
+        }
+        self.write_index()
+    }
+    fn write_index(&self) -> io::Result<()> {
+        let best = self.best();
+        replace_file(self.index_path(), |f| f.write_all(serde_json::to_string(&best).unwrap().as_bytes()))
+    }
+
+    /// The best entry for each (problem, seed), ordered by problem
+    /// and seed.
+    pub fn best(&self) -> Vec<Entry> {
+        self.best.values().cloned().collect()
+    }
+    /// Write the best solutions as one JSON array, ready to submit.
+    pub fn export_best<P: AsRef<Path>>(&self, fname: P) -> io::Result<()> {
+        let sols: Vec<Solution> = self.best.values().map(|e| e.to_solution()).collect();
+        let mut f = File::create(fname)?;
+        f.write_all(serde_json::to_string(&sols).unwrap().as_bytes())
+    }
+}
+
+/// Write `path` anew by writing a file next to it and renaming that
+/// over it, so that a crash or an error partway through leaves the
+/// old one as it was.
+fn replace_file<P, F>(path: P, write: F) -> io::Result<()>
+    where P: AsRef<Path>, F: FnOnce(&mut BufWriter<File>) -> io::Result<()>
+{
+    let path = path.as_ref();
+    let mut name = path.as_os_str().to_owned();
+    name.push(".tmp");
+    let tmp = PathBuf::from(name);
+    let written = File::create(&tmp).and_then(|f| {
+        let mut w = BufWriter::new(f);
+        write(&mut w)?;
+        w.into_inner().map_err(|e| e.into_error())?.sync_all()
+    });
+    match written {
+        Ok(()) => fs::rename(&tmp, path),
+        Err(e) => {
+            let _ = fs::remove_file(&tmp);
+            Err(e)
+        },
+    }
+}
+
+#[test]
+fn archive_keeps_best_per_seed() {
+    let dir = ::std::env::temp_dir().join(format!("davar-archive-test-{}", ::std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let initial = input_to_states(&Input::from_json("problems/problem_0.json"))[0].clone();
+    let sol = |s: &str| Solution { problem_id: 0, seed: 0, tag: None, solution: s.into() };
+    let phrases = vec!["ei!".to_string()];
+    {
+        let mut a = Archive::open(&dir).unwrap();
+        a.add(Entry::new(&sol("lalala"), 1, "alldown", &[], &phrases, &initial)).unwrap();
+        a.add(Entry::new(&sol("ei!lalalalalal"), 2, "alldown", &[], &phrases, &initial)).unwrap();
+        a.add(Entry::new(&sol("la"), 3, "alldown", &[], &phrases, &initial)).unwrap();
+    }
+    let a = Archive::open(&dir).unwrap();
+    assert_eq!(a.entries().unwrap().len(), 3);
+    let best = a.best();
+    assert_eq!(best.len(), 1);
+    assert_eq!(best[0].solution, "ei!lalalalalal");
+    assert!(best[0].power_score > 0);
+    assert_eq!(best[0].total_score, best[0].move_score + best[0].power_score);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn verify_counts_a_phrase_at_the_end() {
+    let dir = ::std::env::temp_dir().join(format!("davar-verify-test-{}", ::std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let initial = input_to_states(&Input::from_json("problems/problem_0.json"))[0].clone();
+    let sol = Solution { problem_id: 0, seed: 0, tag: None, solution: "lei!".into() };
+    let mut a = Archive::open(&dir).unwrap();
+    // As it was archived when we didn't count the "ei!" that ends it.
+    let mut e = Entry::new(&sol, 0, "supplied", &[], &["ei!".to_string()], &initial);
+    e.total_score -= e.power_score;
+    e.power_score = 0;
+    a.add(e).unwrap();
+    let v = a.verify(|_, _| Some(initial.clone())).unwrap();
+    assert_eq!(v.len(), 1);
+    assert_eq!(v[0].ending, simulate::Ending::Clean);
+    assert_eq!(v[0].old_total, v[0].entry.move_score);
+    assert_eq!(v[0].entry.power_score, 2*3 + 300);
+    assert!(v[0].is_suspect());
+    assert_eq!(a.best()[0].total_score, v[0].entry.total_score);
+    assert_eq!(a.entries().unwrap(), vec![v[0].entry.clone()]);
+    assert!(!dir.join("solutions.jsonl.tmp").exists());
+
+    // If we can't write the new archive, the old one is still there.
+    let entries = a.entries().unwrap();
+    assert!(replace_file(dir.join("solutions.jsonl"), |_| Err(io::Error::new(io::ErrorKind::WriteZero, "disk full"))).is_err());
+    assert_eq!(a.entries().unwrap(), entries);
+    assert!(!dir.join("solutions.jsonl.tmp").exists());
+    let _ = fs::remove_dir_all(&dir);
+}
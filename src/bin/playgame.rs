@@ -1,87 +1,212 @@
 extern crate davar;
+extern crate getopts;
 extern crate ncurses;
 
 use ncurses::*;
 use davar::*;
-use davar::Direction::*;
+use davar::game::{Game, Keymap};
+use davar::render::{Mark, Render};
+use davar::screen::{put, put_board, Screen};
+use davar::solver::assist::{self, Placement};
 use davar::Command::{Move, Rotate};
-use std::io::BufRead;
-
-fn main() {
-    print!("Welcome to the game-mode of Davar. The commands are:\n");
-    print!("     a or 4: West\n");
-    print!("     d or 6: East\n");
-    print!("     z or 1: SouthWest\n");
-    print!("     c or 3: SouthEast\n");
-    print!("     s or 5: Rotate Clockwise\n");
-    print!("     x or 2: Rotate Counter-Clockwise\n");
-    print!("\nWelcome to Davar. Please select a problem (0-24):\n");
-
-    let stdin = std::io::stdin();
+use davar::Direction::*;
+use std::env;
+use std::process;
+
+/// What the keys do, for keymap `k`.
+fn help(k: &Keymap) -> String {
+    format!("\
+{}/{}/{}/{} or 4/6/1/3: move   {}/{} or 5/2: rotate   p: type letters or a phrase
+u: undo   r: redo   w: save   q: quit
+o: show where the piece can go   [/]: choose a place   enter: go there",
+            k.key(Move(W)), k.key(Move(E)), k.key(Move(SW)), k.key(Move(SE)),
+            k.key(Rotate(Clock::Wise)), k.key(Rotate(Clock::Counter)))
+}
 
-    let line = stdin.lock().lines().next().unwrap().unwrap();
+/// How long to look for places the piece can go.
+const ASSIST_SECONDS: f64 = 1.0;
 
-    let prob_num: u8 = match line.parse().ok() {
-        Some(n) => if n <= 24 { n } else { println!("Invalid problem number."); return; },
-        None => { println!("Invalid problem number."); return; },
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = getopts::Options::new();
+    opts.optopt("f", "", "problem file to play", "FILENAME");
+    opts.optopt("", "problem", "problem id to play, from the problems directory", "ID");
+    opts.optopt("", "problems", "directory of problems (default \"problems\")", "DIR");
+    opts.optopt("", "seed", "seed to play (default: the problem's first)", "SEED");
+    opts.optmulti("p", "", "phrase of power (default: those in the pop file)", "PHRASE");
+    opts.optopt("", "keymap", "keys for west, east, south-west, south-east, clockwise \
+                               and counter-clockwise (default \"adzcsx\")", "KEYS");
+    opts.optopt("", "style", "how to draw the board: ascii (the default), ansi, hex or hex+ansi", "STYLE");
+    opts.optopt("", "start", "letters to play before handing over, e.g. from a solver log", "LETTERS");
+    opts.optflag("h", "help", "print this help menu");
+    let matches = match opts.parse(&args[1..]) {
+        Ok(m) => { m }
+        Err(f) => { panic!("{}", f) }
+    };
+    let usage = |code| -> ! {
+        let brief = format!("Usage: {} (-f FILENAME | --problem ID) [options]", args[0]);
+        print!("{}\n{}\n", opts.usage(&brief), help(&Keymap::default()));
+        process::exit(code);
+    };
+    if matches.opt_present("h") {
+        usage(0);
+    }
+    let fail = |msg: String| -> ! {
+        println!("{}", msg);
+        process::exit(1);
     };
 
-    let input = Input::from_json(format!("problems/problem_{}.json", prob_num));
-    let states = input_to_states(&input);
-    let seeds: Vec<i32> = states.iter().map(|s| s.seed).collect();
-
-    let seed: i32 = if seeds.len() > 1 {
-
-        let printable_seeds = seeds.iter().map(|s| format!("{}", s)).collect::<Vec<String>>().connect(", ");
-        println!("\nAvailable seeds are: {}", printable_seeds);
-
-        print!("Please enter desired seed:\n");
-        let line = stdin.lock().lines().next().unwrap().unwrap();
-
-        let temp_seed: i32 = match line.parse().ok() {
-            Some(n) => if let Some(_) = seeds.iter().find(|&&s| s == n) { n } else { println!("Invalid seed."); return; },
-            None => { println!("Invalid seed."); return; },
-        };
-        temp_seed
+    let problems = match (matches.opt_str("f"), matches.opt_str("problem")) {
+        (Some(f), None) => problems::Problems::from_files(&[f]),
+        (None, Some(_)) =>
+            problems::Problems::from_dir(matches.opt_str("problems").unwrap_or(problems::DEFAULT_DIR.into())),
+        _ => usage(1),
+    };
+    let problems = problems.unwrap_or_else(|e| fail(e.to_string()));
+    let prob_num = match matches.opt_str("problem") {
+        Some(id) => id.parse().unwrap_or_else(|_| fail(format!("Bad problem id \"{}\".", id))),
+        None => problems.ids()[0],
+    };
+    let input = problems.get(prob_num).unwrap_or_else(|| fail(format!("There is no problem {}.", prob_num)));
+    let seed = match matches.opt_str("seed") {
+        Some(seed) => seed.parse().unwrap_or_else(|_| fail(format!("Bad seed \"{}\".", seed))),
+        None => input.source_seeds[0],
+    };
+    let initial = problems.initial_state(prob_num, seed)
+        .unwrap_or_else(|| fail(format!("Problem {} has no seed {}.", prob_num, seed)));
+    let phrases = if matches.opt_present("p") {
+        matches.opt_strs("p")
     } else {
-        println!("Only one seed for this problem. Using it!");
-        seeds[0]
+        in_out::read_phrases("pop").unwrap_or_default()
+    };
+    let keymap: Keymap = match matches.opt_str("keymap") {
+        Some(k) => k.parse().unwrap_or_else(|e| fail(format!("Bad keymap: {}.", e))),
+        None => Keymap::default(),
+    };
+    let style = match matches.opt_str("style") {
+        Some(s) => s.parse().unwrap_or_else(|e| fail(format!("Bad style: {}.", e))),
+        None => render::Style::default(),
     };
 
-    let mut state: State = states.iter().find(|s| s.seed == seed).unwrap().clone();
-
-    initscr();
-    noecho();
-    curs_set(CURSOR_VISIBILITY::CURSOR_INVISIBLE);
-
-
-    while !state.game_over {
+    let mut game = Game::new(prob_num, initial);
+    let mut message = String::new();
+    if let Some(start) = matches.opt_str("start") {
+        if let Some(c) = start.chars().find(|&c| char_to_command(c).is_none()) {
+            fail(format!("'{}' in --start is not a move.", c));
+        }
+        let n = game.play_str(&start);
+        if n < start.chars().count() {
+            message = format!("The game ended after {} of the {} letters to start with.",
+                              n, start.chars().count());
+        }
+    }
+    // The places the piece can go, and what had been played when we
+    // found them.
+    let mut assisting = false;
+    let mut places: Option<(String, Vec<Placement>)> = None;
+    let mut selected = 0;
+
+    let _screen = Screen::start();
+    loop {
+        let stale = match places {
+            Some(ref p) => p.0 != game.played(),
+            None => true,
+        };
+        if assisting && stale {
+            let found = assist::placements(game.state(), game.played(), &phrases, ASSIST_SECONDS);
+            places = Some((game.played().into(), found));
+            selected = 0;
+        }
         clear();
+        put(&format!("Problem: {}, Seed: {}\n", prob_num, seed));
+        put(&format!("Remaining pieces: {}\n", game.state().unit_sequence.len()));
+        // With the assist on, every place the piece can go, and the
+        // way to the one chosen and what it would clear.
+        let mut board = Render::new(game.state(), style);
+        let chosen = match (assisting, places.as_ref()) {
+            (true, Some((_, found))) => {
+                board.candidates(&found.iter().map(|pl| pl.unit.clone()).collect::<Vec<_>>());
+                if let Some(pl) = found.get(selected) {
+                    board.path(game.state(), &pl.path).clears(&pl.unit).mark(&pl.unit.members, Mark::Selected);
+                }
+                Some((found.get(selected), found.len()))
+            },
+            _ => None,
+        };
+        put_board(&board);
+        let (m, p) = (game.move_score(), game.power_score(&phrases));
+        put(&format!("\nScore: {} + {} power = {}\n", m, p, m + p));
+        match chosen {
+            Some((Some(pl), n)) => put(&format!("Place {}/{}: {} + {} power = {} by {}\n", selected + 1, n,
+                                                pl.move_score, pl.power_score, pl.score(), pl.path)),
+            Some((None, _)) => put("Found nowhere for this piece to go.\n"),
+            None => (),
+        }
+        // As much of the end of what we have played as fits.
+        let width = ::std::cmp::max(10, COLS() as usize - 10);
+        let played: Vec<char> = game.played().chars().collect();
+        let shown: String = played[played.len().saturating_sub(width)..].iter().cloned().collect();
+        put(&format!("Played: {}\n", shown));
+        if game.is_over() {
+            put("Game over!\n");
+        }
+        put(&format!("{}\n\n{}", message, help(&keymap)));
         refresh();
-        printw(&format!("Problem: {}, Seed: {}\n", prob_num, seed));
-        printw(&format!("Remaining pieces: {}\n", state.unit_sequence.len()));
-        printw(&state.visualize());
-        printw(&format!("\nScore: {}\n", state.score));
+        message.clear();
 
-        let ch = getch();
-
-        let cmd = match ch as u8 as char {
-            'a' | '4' => Some(Move(W)),
-            'd' | '6' => Some(Move(E)),
-            'z' | '1' => Some(Move(SW)),
-            'c' | '3' => Some(Move(SE)),
-            's' | '5' => Some(Rotate(Clock::Wise)),
-            'x' | '2' => Some(Rotate(Clock::Counter)),
-            _ => None,
+        let ch = match getch() {
+            KEY_ENTER => '\n' as i32,
+            ch => ch,
+        };
+        let cmd = match ::std::char::from_u32(ch as u32).unwrap_or('\0') {
+            'u' => { if !game.undo() { message = "Nothing to undo.".into(); } None },
+            'r' => { if !game.redo() { message = "Nothing to redo.".into(); } None },
+            'p' => {
+                let letters = screen::prompt("Letters or phrase to play: ");
+                let n = game.play_str(&letters);
+                if n < letters.chars().count() {
+                    message = format!("Played {} of {} letters.", n, letters.chars().count());
+                }
+                None
+            },
+            'w' => { message = save(&game, &phrases); None },
+            'o' => { assisting = !assisting; None },
+            ']' | '[' => {
+                if let Some((_, found)) = places.as_ref() {
+                    if !found.is_empty() {
+                        selected = (selected + if ch == ']' as i32 { 1 } else { found.len() - 1 }) % found.len();
+                    }
+                }
+                None
+            },
+            '\n' | '\r' => {
+                match places.as_ref().and_then(|p| p.1.get(selected)) {
+                    Some(pl) if assisting => { game.play_str(&pl.path); },
+                    _ => message = "Press o to choose a place first.".into(),
+                }
+                None
+            },
+            'q' => break,
+            c => keymap.command(c),
         };
 
         if let Some(c) = cmd {
-            state = state.apply(c);
+            game.play(c.to_char());
         }
     }
-    println!("\nGame over!");
-
-    getch();
+}
 
-    endwin();
+/// Save the game as a solution, in `solutions/` and in the archive,
+/// so it can be submitted or given to a solver to start from.
+fn save(game: &Game, phrases: &[String]) -> String {
+    let score = game.move_score() + game.power_score(phrases);
+    let sol = game.to_solution(Some(format!("playgame[{},{}] = {}", game.problem_id,
+                                            game.initial().seed, score)));
+    in_out::save_solutions(&[(sol.clone(), score)]);
+    let entry = archive::Entry::new(&sol, score, "playgame", &[], phrases, game.initial());
+    match archive::Archive::open(archive::DEFAULT_DIR).and_then(|mut a| a.add(entry)) {
+        Ok(()) => format!("Saved as solutions/{}-{}-{}.json and in the archive.",
+                          sol.problem_id, sol.seed, score),
+        Err(e) => format!("Couldn't save to the archive: {}", e),
+    }
 }
@@ -0,0 +1,196 @@
+extern crate davar;
+extern crate getopts;
+extern crate ncurses;
+
+use ncurses::*;
+use davar::*;
+use davar::render::{Render, Style};
+use davar::simulate::Ending;
+use davar::screen::{put, put_board, put_with, Screen};
+use davar::timeline::Timeline;
+use std::env;
+use std::process;
+
+/// Characters per second we can play at.
+const SPEEDS: [usize; 6] = [1, 4, 16, 64, 256, 1024];
+
+const HELP: &str = "\
+left/right or h/l: back/forward a move   up/down or k/j: back/forward a piece
+home/end: start/end   g: go to move   space: play/pause   s: speed
+p/n: previous/next solution   q: quit";
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut opts = getopts::Options::new();
+    opts.optopt("", "problems", "directory of problems (default \"problems\")", "DIR");
+    opts.optmulti("f", "", "problem file, instead of a directory of them", "FILENAME");
+    opts.optmulti("p", "", "phrase of power (default: those in the pop file)", "PHRASE");
+    opts.optopt("", "style", "how to draw the board: ascii (the default), ansi, hex or hex+ansi", "STYLE");
+    opts.optopt("", "solution", "which solution in the file to start with (default 0)", "INDEX");
+    opts.optflag("h", "help", "print this help menu");
+    let matches = match opts.parse(&args[1..]) {
+        Ok(m) => { m }
+        Err(f) => { panic!("{}", f) }
+    };
+    if matches.opt_present("h") || matches.free.len() != 1 {
+        let brief = format!("Usage: {} [options] SOLUTIONS.json", args[0]);
+        print!("{}\n{}\n", opts.usage(&brief), HELP);
+        process::exit(if matches.opt_present("h") { 0 } else { 1 });
+    }
+
+    let solutions = match in_out::load_solutions(&matches.free[0]) {
+        Ok(sols) => sols,
+        Err(e) => { println!("Couldn't read {}: {}", matches.free[0], e); process::exit(1); },
+    };
+    let files = matches.opt_strs("f");
+    let problems = if !files.is_empty() {
+        problems::Problems::from_files(&files)
+    } else {
+        problems::Problems::from_dir(matches.opt_str("problems").unwrap_or(problems::DEFAULT_DIR.into()))
+    };
+    let problems = match problems {
+        Ok(ps) => ps,
+        Err(e) => { println!("{}", e); process::exit(1); },
+    };
+    for s in solutions.iter() {
+        if problems.initial_state(s.problem_id, s.seed).is_none() {
+            println!("We have no problem {} with seed {}.", s.problem_id, s.seed);
+            process::exit(1);
+        }
+    }
+    if solutions.is_empty() {
+        println!("No solutions in {}.", matches.free[0]);
+        process::exit(1);
+    }
+    let phrases = if matches.opt_present("p") {
+        matches.opt_strs("p")
+    } else {
+        in_out::read_phrases("pop").unwrap_or_default()
+    };
+    let style: Style = match matches.opt_str("style").map(|s| s.parse()) {
+        Some(Ok(s)) => s,
+        Some(Err(e)) => { println!("{}", e); process::exit(1); },
+        None => Style::default(),
+    };
+    let timeline = |i: usize| {
+        let s = &solutions[i];
+        Timeline::new(s, &problems.initial_state(s.problem_id, s.seed).unwrap(), &phrases)
+    };
+
+    let mut which: usize = match matches.opt_str("solution").map(|i| i.parse::<usize>()) {
+        Some(Ok(i)) if i < solutions.len() => i,
+        Some(Ok(i)) => {
+            println!("--solution {}: {} holds solutions 0 to {}", i, matches.free[0], solutions.len() - 1);
+            process::exit(1);
+        },
+        Some(Err(e)) => { println!("--solution: {}", e); process::exit(1); },
+        None => 0,
+    };
+    let mut t = timeline(which);
+    let mut pos = 0;
+    let mut playing = false;
+    let mut speed = 2;
+
+    let _screen = Screen::start();
+    loop {
+        draw(&t, pos, which, solutions.len(), if playing { Some(SPEEDS[speed]) } else { None }, style);
+        // While playing, redraw at most 50 times a second, taking as
+        // many moves at a time as we need to keep up.
+        let tick = ::std::cmp::max(20, 1000/SPEEDS[speed]);
+        timeout(if playing { tick as i32 } else { -1 });
+        let ch = getch();
+        if ch == ERR {
+            pos = ::std::cmp::min(t.len(), pos + ::std::cmp::max(1, SPEEDS[speed]*tick/1000));
+            if pos == t.len() {
+                playing = false;
+            }
+            continue;
+        }
+        match ch {
+            KEY_RIGHT => pos = ::std::cmp::min(t.len(), pos + 1),
+            KEY_LEFT => pos = pos.saturating_sub(1),
+            KEY_DOWN => pos = t.next_piece(pos),
+            KEY_UP => pos = t.prev_piece(pos),
+            KEY_HOME => pos = 0,
+            KEY_END => pos = t.len(),
+            _ => match ::std::char::from_u32(ch as u32) {
+                Some('l') => pos = ::std::cmp::min(t.len(), pos + 1),
+                Some('h') => pos = pos.saturating_sub(1),
+                Some('j') => pos = t.next_piece(pos),
+                Some('k') => pos = t.prev_piece(pos),
+                Some(' ') => {
+                    if pos == t.len() {
+                        pos = 0;
+                    }
+                    playing = !playing;
+                },
+                Some('s') => speed = (speed + 1) % SPEEDS.len(),
+                Some('g') => {
+                    timeout(-1);
+                    let answer = screen::prompt(&format!("Go to move (0-{}): ", t.len()));
+                    if let Ok(i) = answer.trim().parse::<usize>() {
+                        pos = ::std::cmp::min(i, t.len());
+                    }
+                },
+                Some('n') | Some('p') => {
+                    let next = if ch == 'n' as i32 { which + 1 } else { which + solutions.len() - 1 };
+                    which = next % solutions.len();
+                    t = timeline(which);
+                    pos = 0;
+                    playing = false;
+                },
+                Some('q') => break,
+                _ => (),
+            },
+        }
+    }
+}
+
+fn draw(t: &Timeline, pos: usize, which: usize, nsolutions: usize, playing: Option<usize>, style: Style) {
+    let s = &t.solution;
+    clear();
+    put(&format!("Problem {}, seed {}, solution {}/{}: {}\n", s.problem_id, s.seed,
+                 which + 1, nsolutions, s.tag.clone().unwrap_or("(no tag)".into())));
+    let pieces = t.state(0).unit_sequence.len();
+    put(&format!("Move {}/{}, piece {}/{}, {}\n", pos, t.len(), t.piece(pos), pieces,
+                 match playing {
+                     Some(speed) => format!("playing {} moves a second", speed),
+                     None => "paused".into(),
+                 }));
+    // Where the piece goes from here.
+    let rest: String = t.solution.solution.chars().skip(pos).collect();
+    put_board(Render::new(t.state(pos), style).path(t.state(pos), &rest));
+    let (m, p) = (t.move_score(pos), t.power_score(pos));
+    put(&format!("\nMove score {} + power score {} = {}\n", m, p, m + p));
+    // Like --verify, which scores such a solution as nothing at all.
+    match t.ending() {
+        Ending::Clean => put("\n"),
+        Ending::Revisit(i) => put(&format!("Move {} revisits a position, so the solution scores nothing.\n", i)),
+        Ending::Trailing(i) => put(&format!("The game is over before move {}, so the solution scores nothing.\n", i)),
+    }
+    if pos < t.len() {
+        put(&format!("Next: '{}'", t.char_at(pos)));
+        if let Some(phrase) = t.phrase_at(pos) {
+            put(&format!(", part of \"{}\"", phrase));
+        }
+    }
+    put("\n");
+
+    // The solution around where we are, with phrases of power
+    // underlined and the next move highlighted.
+    let width = ::std::cmp::max(10, COLS() as usize - 1);
+    let start = ::std::cmp::min(pos.saturating_sub(width/2), t.len().saturating_sub(width));
+    for i in start .. ::std::cmp::min(t.len(), start + width) {
+        let c = t.char_at(i).to_string();
+        if i == pos {
+            put_with(A_REVERSE, &c);
+        } else if t.phrase_at(i).is_some() {
+            put_with(A_UNDERLINE, &c);
+        } else {
+            put(&c);
+        }
+    }
+    put("\n\n");
+    put(HELP);
+    refresh();
+}
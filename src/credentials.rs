@@ -0,0 +1,127 @@
+//! Where the contest API token comes from.
+//!
+//! The token must never be committed, so we look for it in the
+//! `DAVAR_API_TOKEN` environment variable, and failing that in a file
+//! (`--credentials`, `$DAVAR_CREDENTIALS` or `~/.davar-token`).  Like
+//! ssh keys, the file must not be readable by anyone but its owner.
+//!
+//! The token that used to be in the scripts is still in the git
+//! history, so it is compromised and has to be rotated (see README).
+
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct Credentials {
+    pub token: String,
+}
+
+#[derive(Debug)]
+pub enum CredentialsError {
+    Missing(PathBuf),
+    Unreadable(PathBuf, io::Error),
+    TooOpen(PathBuf, u32),
+    Empty(PathBuf),
+}
+
+impl fmt::Display for CredentialsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CredentialsError::Missing(ref p) =>
+                write!(f, "no API token: set DAVAR_API_TOKEN or put the token in {} (chmod 600)",
+                       p.display()),
+            CredentialsError::Unreadable(ref p, ref e) =>
+                write!(f, "couldn't read API token from {}: {}", p.display(), e),
+            CredentialsError::TooOpen(ref p, mode) =>
+                write!(f, "{} has mode {:o}, but must not be readable by others (chmod 600)",
+                       p.display(), mode),
+            CredentialsError::Empty(ref p) =>
+                write!(f, "{} does not contain an API token", p.display()),
+        }
+    }
+}
+
+impl Error for CredentialsError {}
+
+/// The file we read the token from, unless told otherwise.
+pub fn default_path() -> PathBuf {
+    match env::var("DAVAR_CREDENTIALS") {
+        Ok(p) => p.into(),
+        Err(_) => PathBuf::from(env::var("HOME").unwrap_or(".".into())).join(".davar-token"),
+    }
+}
+
+/// Find the API token, from the environment or from `file` (or the
+/// default file if that is `None`).
+pub fn load(file: Option<&Path>) -> Result<Credentials, CredentialsError> {
+    if let Ok(token) = env::var("DAVAR_API_TOKEN") {
+        if !token.trim().is_empty() {
+            return Ok(Credentials { token: token.trim().into() });
+        }
+    }
+    let path = match file {
+        Some(p) => p.to_path_buf(),
+        None => default_path(),
+    };
+    from_file(&path)
+}
+
+pub fn from_file(path: &Path) -> Result<Credentials, CredentialsError> {
+    let meta = match fs::metadata(path) {
+        Ok(m) => m,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound =>
+            return Err(CredentialsError::Missing(path.to_path_buf())),
+        Err(e) => return Err(CredentialsError::Unreadable(path.to_path_buf(), e)),
+    };
+    check_permissions(path, &meta)?;
+    let mut token = String::new();
+    if let Err(e) = File::open(path).and_then(|mut f| f.read_to_string(&mut token)) {
+        return Err(CredentialsError::Unreadable(path.to_path_buf(), e));
+    }
+    let token = token.trim();
+    if token.is_empty() {
+        return Err(CredentialsError::Empty(path.to_path_buf()));
+    }
+    Ok(Credentials { token: token.into() })
+}
+
+#[cfg(unix)]
+fn check_permissions(path: &Path, meta: &fs::Metadata) -> Result<(), CredentialsError> {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = meta.permissions().mode() & 0o777;
+    if mode & 0o077 != 0 {
+        return Err(CredentialsError::TooOpen(path.to_path_buf(), mode));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn check_permissions(_path: &Path, _meta: &fs::Metadata) -> Result<(), CredentialsError> {
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn token_file_must_be_private() {
+    use std::io::Write;
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = env::temp_dir().join(format!("davar-token-test-{}", ::std::process::id()));
+    File::create(&path).unwrap().write_all(b"abc123\n").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+    match from_file(&path) {
+        Err(CredentialsError::TooOpen(_, 0o644)) => (),
+        x => panic!("expected TooOpen, got {:?}", x),
+    }
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+    assert_eq!(from_file(&path).unwrap().token, "abc123");
+    fs::remove_file(&path).unwrap();
+    match from_file(&path) {
+        Err(CredentialsError::Missing(_)) => (),
+        x => panic!("expected Missing, got {:?}", x),
+    }
+}
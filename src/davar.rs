@@ -1,18 +1,40 @@
 extern crate num;
-extern crate rustc_serialize;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde;
+extern crate serde_json;
 extern crate time;
+extern crate ncurses;
+extern crate png;
+extern crate gif;
 
 use std::vec::Vec;
 use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
 
 pub mod simulate;
 pub mod opts;
 pub mod in_out;
 pub mod solver;
+pub mod memory;
+pub mod schedule;
+pub mod anytime;
+pub mod archive;
+pub mod submit;
+pub mod credentials;
+pub mod report;
+pub mod problems;
+pub mod timeline;
+pub mod screen;
+pub mod game;
+pub mod render;
+pub mod export;
+pub mod html;
+pub mod trace;
 
 pub type Score = i32;
 
-#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash, RustcDecodable, RustcEncodable)]
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash, Serialize, Deserialize)]
 pub struct Cell {
     pub x: i32,
     pub y: i32,
@@ -20,11 +42,11 @@ pub struct Cell {
 
 impl Cell {
   fn new(x: i32, y:i32) -> Cell {
-    Cell{x: x, y: y}
+    Cell{x, y}
   }
 }
 
-#[derive(Debug, Eq, Clone, Hash, RustcDecodable, RustcEncodable)]
+#[derive(Debug, Eq, Clone, Serialize, Deserialize)]
 pub struct Unit {
     pub members: Vec<Cell>,
     pub pivot: Cell,
@@ -36,6 +58,12 @@ impl PartialEq<Unit> for Unit {
         self.pivot == other.pivot && set_a == set_b
     }
 }
+impl Hash for Unit {
+    // Equal units may list their members in any order.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.pivot.hash(state);
+    }
+}
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
 pub enum Direction { W, E, SW, SE }
@@ -64,22 +92,23 @@ impl Command {
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Clone, Hash, RustcDecodable, RustcEncodable)]
-#[allow(non_snake_case)]
+#[derive(Debug, Eq, PartialEq, Clone, Hash, Serialize, Deserialize)]
 pub struct Input {
     pub id: i32,
     pub units: Vec<Unit>,
     pub width: i32,
     pub height: i32,
     pub filled: Vec<Cell>,
-    pub sourceLength: i32,
-    pub sourceSeeds: Vec<i32>,
+    #[serde(rename = "sourceLength")]
+    pub source_length: i32,
+    #[serde(rename = "sourceSeeds")]
+    pub source_seeds: Vec<i32>,
 }
 
-#[derive(Debug, Eq, PartialEq, Clone, Hash, RustcEncodable)]
-#[allow(non_snake_case)]
+#[derive(Debug, Eq, PartialEq, Clone, Hash, Serialize, Deserialize)]
 pub struct Solution {
-    pub problemId: i32,
+    #[serde(rename = "problemId")]
+    pub problem_id: i32,
     pub seed: i32,
     pub tag: Option<String>,
     pub solution: String,
@@ -102,8 +131,8 @@ impl State {
     fn with_size(width: i32, height: i32) -> State {
         State {
             seed: 0,
-            width: width,
-            height: height,
+            width,
+            height,
             filled_array: vec![false; (width*height) as usize],
             visited: Vec::with_capacity(3*width as usize),
             unit_sequence: Vec::new(),
@@ -126,75 +155,9 @@ impl State {
         self.filled_array[c.x as usize + (c.y as usize)*(self.width as usize)]
     }
 
+    /// The board in plain ASCII; see `render` for more.
     pub fn visualize(&self) -> String {
-
-        // print stuff here, but eventually return a string.
-        let mut chars: Vec<Vec<char>> = vec![vec![]; self.width as usize];
-        for i in 0 .. self.width as usize {
-            chars[i] = vec!['O'; self.height as usize];
-        }
-
-        let mut out_str = Vec::with_capacity( (2*self.width + 4) as usize * self.height as usize);
-        if self.unit_sequence.len() > 0 {
-            let ref unit_array = self.unit_sequence[0].members;
-            let ref pivot = self.unit_sequence[0].pivot;
-
-            for y in 0..self.height as usize {
-              out_str.push('|' as u8);
-              if y%2==1 { out_str.push(' ' as u8); }
-
-              for x in 0..self.width as usize {
-                out_str.push(' ' as u8);
-
-                let here = Cell::new(x as i32, y as i32);
-                if self.is_filled(here) {    // Is cell filled?
-                    out_str.push('X' as u8);
-                } else if *pivot == here {   // No, is cell a pivot?
-                    if unit_array.contains(&here) {
-                        out_str.push('#' as u8);
-                    } else {
-                        out_str.push('*' as u8);
-                    }
-                } else {
-                    if !unit_array.contains(&here) {
-                        out_str.push('.' as u8);
-                    }
-                    else {
-                        out_str.push('O' as u8);
-                    }
-                }
-              }
-              if y%2==0 {
-                  out_str.push(' ' as u8);
-              }
-              out_str.push('|' as u8);
-              out_str.push('\n' as u8);
-            }
-        }
-        else {
-            for y in 0..self.height as usize {
-              out_str.push('|' as u8);
-              if y%2==1 { out_str.push(' ' as u8); }
-
-              for x in 0..self.width as usize {
-                out_str.push(' ' as u8);
-
-                if self.is_filled(Cell::new(x as i32, y as i32)) {
-                    out_str.push('X' as u8);
-                }
-                else {
-                    out_str.push('.' as u8);
-                }
-              }
-              if y%2==0 {
-                  out_str.push(' ' as u8);
-              }
-              out_str.push('|' as u8);
-              out_str.push('\n' as u8);
-            }
-        }
-
-        String::from_utf8(out_str).unwrap()
+        render::Render::new(self, render::Style::default()).to_string()
     }
 
   }
@@ -202,8 +165,7 @@ impl State {
 pub fn input_to_states(input: &Input) -> Vec<State> {
     use simulate::Lattice;
     let mut good_units = input.units.clone();
-    for i in 0 .. good_units.len() {
-        let u = &mut good_units[i];
+    for u in good_units.iter_mut() {
         // place the units in the proper location
         let mut miny = u.members[0].y;
         for m in u.members.iter() {
@@ -228,20 +190,16 @@ pub fn input_to_states(input: &Input) -> Vec<State> {
         }
         let widr = input.width - 1 - maxx;
         let widl = minx;
-        let xoff = if widl < widr {
-            (widl - widr)/2
-        } else {
-            (widl - widr)/2
-        };
+        let xoff = (widl - widr)/2;
         u.pivot.x -= xoff;
         for j in 0 .. u.members.len() {
             u.members[j].x -= xoff;
         }
     }
-    input.sourceSeeds.iter().map( |&s| {
-        let mut seq: Vec<Unit> = Vec::with_capacity(input.sourceLength as usize);
-        for i in get_source_order(s, input.sourceLength) {
-            seq.push(good_units[((i as usize) % input.units.len()) as usize].clone());
+    input.source_seeds.iter().map( |&s| {
+        let mut seq: Vec<Unit> = Vec::with_capacity(input.source_length as usize);
+        for i in get_source_order(s, input.source_length) {
+            seq.push(good_units[(i as usize) % input.units.len()].clone());
         }
         let mut state = State::with_size(input.width, input.height);
         state.unit_sequence = seq;
@@ -253,20 +211,38 @@ pub fn input_to_states(input: &Input) -> Vec<State> {
     }).collect()
 }
 
-pub fn string_to_commands(s: &str) -> Vec<Command> {
+/// The command a character of a solution stands for, if any.
+pub fn char_to_command(c: char) -> Option<Command> {
     use Direction::*;
+    match c.to_lowercase().next().unwrap() {
+        'p' | '\'' | '!' | '.' | '0' | '3' => Some(Command::Move(W)),
+        'b' | 'c' | 'e' | 'f' | 'y' | '2' => Some(Command::Move(E)),
+        'a' | 'g' | 'h' | 'i' | 'j' | '4' => Some(Command::Move(SW)),
+        'l' | 'm' | 'n' | 'o' | ' ' | '5' => Some(Command::Move(SE)),
+        'd' | 'q' | 'r' | 'v' | 'z' | '1' => Some(Command::Rotate(Clock::Wise)),
+        'k' | 's' | 't' | 'u' | 'w' | 'x' => Some(Command::Rotate(Clock::Counter)),
+        _ => None,
+    }
+}
+
+/// The first character of `s` that is not a command, and where it
+/// is, if there is one.  Whitespace other than spaces is ignored.
+pub fn bad_command(s: &str) -> Option<(usize, char)> {
+    s.chars().enumerate().find(|&(_, c)| {
+        char_to_command(c).is_none() && c != '\t' && c != '\n' && c != '\r'
+    })
+}
+
+/// The commands in `s`, which must not have a `bad_command`: check
+/// text from files or the command line first.
+pub fn string_to_commands(s: &str) -> Vec<Command> {
     let mut out = Vec::new();
     for c in s.chars() {
-        match c.to_lowercase().next().unwrap() {
-            'p' | '\'' | '!' | '.' | '0' | '3' => out.push(Command::Move(W)),
-            'b' | 'c' | 'e' | 'f' | 'y' | '2' => out.push(Command::Move(E)),
-            'a' | 'g' | 'h' | 'i' | 'j' | '4' => out.push(Command::Move(SW)),
-            'l' | 'm' | 'n' | 'o' | ' ' | '5' => out.push(Command::Move(SE)),
-            'd' | 'q' | 'r' | 'v' | 'z' | '1' => out.push(Command::Rotate(Clock::Wise)),
-            'k' | 's' | 't' | 'u' | 'w' | 'x' => out.push(Command::Rotate(Clock::Counter)),
-            '\t' | '\n' | '\r' => (),
-            x => {
-                println!("bad char '{}' from \"{}\"", x, s);
+        match char_to_command(c) {
+            Some(cmd) => out.push(cmd),
+            None if c == '\t' || c == '\n' || c == '\r' => (),
+            None => {
+                println!("bad char '{}' from \"{}\"", c, s);
                 unreachable!()
             },
         };
@@ -0,0 +1,337 @@
+//! Boards and replays as pictures: a board as SVG, and a solution
+//! played out as PNG frames or an animated GIF.
+//!
+//! Cells are drawn as pointy-topped hexagons, with odd rows shifted
+//! half a cell to the right just as `render` draws them, and colored
+//! by how they look there, annotations and all.
+
+use super::*;
+use render::{Look, Mark, Render, Style};
+
+use gif;
+use png;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Pixels from the middle of a cell to a corner.
+pub const RADIUS: f64 = 10.0;
+/// Pixels around the board.
+const MARGIN: f64 = 4.0;
+
+/// The colors of our pictures.  The background is color 0, and
+/// each look has a color of its own after it.
+const PALETTE: [[u8; 3]; 10] = [
+    [0xff, 0xff, 0xff], // background
+    [0xe4, 0xe4, 0xe4], // empty
+    [0x2c, 0x4a, 0x8a], // filled
+    [0xf0, 0xa0, 0x30], // piece
+    [0xd0, 0x30, 0x30], // pivot
+    [0xf0, 0x90, 0x90], // bare pivot
+    [0x9c, 0xd8, 0x9c], // candidate
+    [0xa8, 0xdc, 0xf0], // path
+    [0xc0, 0x40, 0xc0], // clear
+    [0x20, 0x90, 0x20], // selected
+];
+
+fn color(look: Look) -> u8 {
+    match look {
+        Look::Empty => 1,
+        Look::Filled => 2,
+        Look::Piece => 3,
+        Look::Pivot => 4,
+        Look::BarePivot => 5,
+        Look::Marked(Mark::Candidate) => 6,
+        Look::Marked(Mark::Path) => 7,
+        Look::Marked(Mark::Clear) => 8,
+        Look::Marked(Mark::Selected) => 9,
+    }
+}
+
+/// The color of `look`, as CSS or SVG want it.
+pub fn hex_color(look: Look) -> String {
+    let c = PALETTE[color(look) as usize];
+    format!("#{:02x}{:02x}{:02x}", c[0], c[1], c[2])
+}
+
+/// What to write for each solution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// The board once the solution is played.
+    Svg,
+    /// A picture of the board after every character.
+    Png,
+    /// An animated GIF of the whole game.
+    Gif,
+    /// A page to step through the game in a browser.
+    Html,
+}
+
+impl FromStr for Format {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Format, String> {
+        match s {
+            "svg" => Ok(Format::Svg),
+            "png" => Ok(Format::Png),
+            "gif" => Ok(Format::Gif),
+            "html" => Ok(Format::Html),
+            _ => Err(format!("can't export as \"{}\" (try svg, png, gif or html)", s)),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match *self {
+            Format::Svg => "svg",
+            Format::Png => "png",
+            Format::Gif => "gif",
+            Format::Html => "html",
+        })
+    }
+}
+
+/// The size in pixels of a picture of `s`.
+fn size(s: &State) -> (u32, u32) {
+    let w = 3f64.sqrt()*RADIUS;
+    ((2.0*MARGIN + w*(s.width as f64 + 0.5)).ceil() as u32,
+     (2.0*MARGIN + 2.0*RADIUS + 1.5*RADIUS*(s.height - 1).max(0) as f64).ceil() as u32)
+}
+
+/// Where the middle of cell `c` is.
+fn center(c: Cell) -> (f64, f64) {
+    let w = 3f64.sqrt()*RADIUS;
+    (MARGIN + w*(c.x as f64 + 0.5 + 0.5*(c.y % 2) as f64),
+     MARGIN + RADIUS + 1.5*RADIUS*c.y as f64)
+}
+
+/// The board as an SVG document.
+pub fn svg(r: &Render) -> String {
+    let s = r.state();
+    let (width, height) = size(s);
+    let mut out = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+                           viewBox=\"0 0 {} {}\">\n", width, height, width, height);
+    out += &format!("<rect width=\"{}\" height=\"{}\" fill=\"#ffffff\"/>\n", width, height);
+    for y in 0 .. s.height {
+        for x in 0 .. s.width {
+            let c = Cell::new(x, y);
+            let (cx, cy) = center(c);
+            // A little smaller than the cell, to leave a gap between.
+            let corners: Vec<String> = (0..6).map(|i| {
+                let angle = ::std::f64::consts::PI/3.0*i as f64 + ::std::f64::consts::PI/6.0;
+                format!("{:.2},{:.2}", cx + 0.92*RADIUS*angle.cos(), cy + 0.92*RADIUS*angle.sin())
+            }).collect();
+            out += &format!("<polygon points=\"{}\" fill=\"{}\"/>\n", corners.join(" "), hex_color(r.look(c)));
+        }
+    }
+    out += "</svg>\n";
+    out
+}
+
+/// A picture, as indices into our palette.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+/// The board as a picture.
+pub fn image(r: &Render) -> Image {
+    let s = r.state();
+    let (width, height) = size(s);
+    let mut pixels = vec![0; (width*height) as usize];
+    let half_width = 3f64.sqrt()/2.0*0.92*RADIUS;
+    for y in 0 .. s.height {
+        for x in 0 .. s.width {
+            let c = Cell::new(x, y);
+            let (cx, cy) = center(c);
+            let col = color(r.look(c));
+            for py in (cy - RADIUS) as u32 .. (cy + RADIUS).ceil() as u32 {
+                for px in (cx - half_width) as u32 .. (cx + half_width).ceil() as u32 {
+                    // Inside the hexagon through the middle of the pixel?
+                    let dx = (px as f64 + 0.5 - cx).abs();
+                    let dy = (py as f64 + 0.5 - cy).abs();
+                    if dx <= half_width && dy <= 0.92*RADIUS - dx/3f64.sqrt() && px < width && py < height {
+                        pixels[(py*width + px) as usize] = col;
+                    }
+                }
+            }
+        }
+    }
+    Image { width, height, pixels }
+}
+
+fn flat_palette() -> Vec<u8> {
+    PALETTE.iter().flat_map(|c| c.iter().cloned()).collect()
+}
+
+pub fn write_png<P: AsRef<Path>>(path: P, img: &Image) -> io::Result<()> {
+    let mut e = png::Encoder::new(BufWriter::new(File::create(path)?), img.width, img.height);
+    e.set_color(png::ColorType::Indexed);
+    e.set_depth(png::BitDepth::Eight);
+    e.set_palette(flat_palette());
+    e.write_header()?.write_image_data(&img.pixels)?;
+    Ok(())
+}
+
+fn gif_error(e: gif::EncodingError) -> io::Error {
+    match e {
+        gif::EncodingError::Io(e) => e,
+        e => io::Error::new(io::ErrorKind::InvalidData, e),
+    }
+}
+
+/// An animated GIF, written a frame at a time.
+pub struct Gif {
+    encoder: gif::Encoder<BufWriter<File>>,
+}
+
+impl Gif {
+    pub fn create<P: AsRef<Path>>(path: P, width: u32, height: u32) -> io::Result<Gif> {
+        let mut encoder = gif::Encoder::new(BufWriter::new(File::create(path)?),
+                                            width as u16, height as u16, &flat_palette()).map_err(gif_error)?;
+        encoder.set_repeat(gif::Repeat::Infinite).map_err(gif_error)?;
+        Ok(Gif { encoder })
+    }
+    /// Show `img` for `delay_ms` milliseconds.  GIFs count in
+    /// hundredths of a second, and browsers slow down anything
+    /// faster than 20 ms.
+    pub fn add(&mut self, img: &Image, delay_ms: u32) -> io::Result<()> {
+        let mut frame = gif::Frame::from_indexed_pixels(img.width as u16, img.height as u16,
+                                                        &img.pixels[..], None);
+        frame.delay = ::std::cmp::max(2, delay_ms/10) as u16;
+        self.encoder.write_frame(&frame).map_err(gif_error)
+    }
+}
+
+/// Write the board `s` of problem `problem_id` into `dir` as
+/// `format`: `ID-SEED.svg`, `ID-SEED.png`, or a GIF of the one frame.
+/// A page needs a game to step through, so there is no HTML of a
+/// board alone.
+pub fn export_board(s: &State, problem_id: i32, dir: &Path, format: Format) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let name = format!("{}-{}", problem_id, s.seed);
+    let r = Render::new(s, Style::default());
+    match format {
+        Format::Svg => {
+            let path = dir.join(format!("{}.svg", name));
+            File::create(&path)?.write_all(svg(&r).as_bytes())?;
+            Ok(path)
+        },
+        Format::Png => {
+            let path = dir.join(format!("{}.png", name));
+            write_png(&path, &image(&r))?;
+            Ok(path)
+        },
+        Format::Gif => {
+            let path = dir.join(format!("{}.gif", name));
+            let img = image(&r);
+            Gif::create(&path, img.width, img.height)?.add(&img, 0)?;
+            Ok(path)
+        },
+        Format::Html => Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                           "can't export a board alone as html (try svg, png or gif)")),
+    }
+}
+
+/// Write `sol` into `dir` as `format`, with `delay_ms` between frames
+/// of a GIF and `phrases` highlighted in a page.  Each frame shows
+/// where the piece is about to go.  Returns what was written:
+/// `ID-SEED.svg`, `ID-SEED.gif`, `ID-SEED.html`, or a directory
+/// `ID-SEED` of numbered PNGs.
+pub fn export(sol: &Solution, problems: &problems::Problems, phrases: &[String], dir: &Path,
+              format: Format, delay_ms: u32) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let name = format!("{}-{}", sol.problem_id, sol.seed);
+    // The replay can't stop for errors, so we keep the first.
+    let mut error = None;
+    let path = match format {
+        Format::Svg => {
+            let mut last = None;
+            sol.replay(problems, |_, state, _| last = Some(state.clone()));
+            export_board(&last.unwrap(), sol.problem_id, dir, Format::Svg)?
+        },
+        Format::Png => {
+            let path = dir.join(&name);
+            fs::create_dir_all(&path)?;
+            let mut n = 0;
+            sol.replay(problems, |_, state, rest| {
+                let img = image(Render::new(state, Style::default()).path(state, rest));
+                if error.is_none() {
+                    error = write_png(path.join(format!("{:05}.png", n)), &img).err();
+                }
+                n += 1;
+            });
+            path
+        },
+        Format::Gif => {
+            let path = dir.join(format!("{}.gif", name));
+            let mut gif = None;
+            sol.replay(problems, |_, state, rest| {
+                let img = image(Render::new(state, Style::default()).path(state, rest));
+                if gif.is_none() && error.is_none() {
+                    match Gif::create(&path, img.width, img.height) {
+                        Ok(g) => gif = Some(g),
+                        Err(e) => error = Some(e),
+                    }
+                }
+                if let (Some(g), None) = (gif.as_mut(), error.as_ref()) {
+                    error = g.add(&img, delay_ms).err();
+                }
+            });
+            path
+        },
+        Format::Html => {
+            let path = dir.join(format!("{}.html", name));
+            File::create(&path)?.write_all(html::page(sol, problems, phrases).as_bytes())?;
+            path
+        },
+    };
+    match error {
+        Some(e) => Err(e),
+        None => Ok(path),
+    }
+}
+
+#[test]
+fn export_pictures() {
+    let ps = problems::Problems::from_files(&["problems/problem_1.json"]).unwrap();
+    let s = ps.initial_state(1, 0).unwrap();
+    let picture = svg(&Render::new(&s, Style::default()));
+    assert_eq!(picture.matches("<polygon").count(), (s.width*s.height) as usize);
+    assert!(picture.contains(&hex_color(Look::Filled)));
+    assert!(picture.contains(&hex_color(Look::Pivot)));
+
+    let img = image(&Render::new(&s, Style::default()));
+    assert_eq!(img.pixels.len(), (img.width*img.height) as usize);
+    // The middle of every cell is the cell's color.
+    for y in 0 .. s.height {
+        for x in 0 .. s.width {
+            let c = Cell::new(x, y);
+            let (cx, cy) = center(c);
+            let want = color(Render::new(&s, Style::default()).look(c));
+            assert_eq!(img.pixels[(cy as u32*img.width + cx as u32) as usize], want);
+        }
+    }
+
+    let dir = ::std::env::temp_dir().join(format!("davar-export-{}", ::std::process::id()));
+    let sol = Solution { problem_id: 1, seed: 0, tag: None, solution: "ei!lllaaaal".into() };
+    let gif = export(&sol, &ps, &[], &dir, Format::Gif, 100).unwrap();
+    assert!(fs::read(&gif).unwrap().starts_with(b"GIF89a"));
+    let pngs = export(&sol, &ps, &[], &dir, Format::Png, 100).unwrap();
+    assert_eq!(fs::read_dir(&pngs).unwrap().count(), sol.solution.len() + 1);
+    assert!(fs::read(pngs.join("00000.png")).unwrap().starts_with(b"\x89PNG"));
+    let board = export(&sol, &ps, &[], &dir, Format::Svg, 100).unwrap();
+    let page = export(&sol, &ps, &[], &dir, Format::Html, 100).unwrap();
+    assert!(fs::read_to_string(&page).unwrap().starts_with("<!DOCTYPE html>"));
+    assert!(fs::read_to_string(&board).unwrap().starts_with("<svg"));
+    // A board on its own, without playing anything.
+    let start = export_board(&s, 1, &dir, Format::Png).unwrap();
+    assert_eq!(start, dir.join("1-0.png"));
+    assert!(fs::read(&start).unwrap().starts_with(b"\x89PNG"));
+    assert!(export_board(&s, 1, &dir, Format::Html).is_err());
+    fs::remove_dir_all(&dir).unwrap();
+}
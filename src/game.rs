@@ -0,0 +1,202 @@
+//! A game played by hand, with undo and redo.
+
+use super::*;
+use std::str::FromStr;
+
+pub struct Game {
+    pub problem_id: i32,
+    /// `states[i]` is the state after the first `i` letters played.
+    states: Vec<State>,
+    played: String,
+    /// Letters undone, the next to redo last.
+    undone: Vec<char>,
+}
+
+impl Game {
+    pub fn new(problem_id: i32, initial: State) -> Game {
+        Game {
+            problem_id,
+            states: vec![initial],
+            played: String::new(),
+            undone: Vec::new(),
+        }
+    }
+    pub fn state(&self) -> &State {
+        self.states.last().unwrap()
+    }
+    pub fn initial(&self) -> &State {
+        &self.states[0]
+    }
+    /// Everything played so far, as it would be submitted.
+    pub fn played(&self) -> &str {
+        &self.played
+    }
+    pub fn is_over(&self) -> bool {
+        self.state().game_over || self.state().unit_sequence.is_empty()
+    }
+
+    /// Play letter `c`, if it is a command and the game is still on.
+    pub fn play(&mut self, c: char) -> bool {
+        if !self.step(c) {
+            return false;
+        }
+        self.undone.clear();
+        true
+    }
+    /// Play all of `letters` (e.g. a phrase of power), or as many as
+    /// we can before the game ends.  Returns how many were played.
+    pub fn play_str(&mut self, letters: &str) -> usize {
+        if letters.chars().any(|c| char_to_command(c).is_none()) {
+            return 0;
+        }
+        let mut n = 0;
+        for c in letters.chars() {
+            if !self.play(c) {
+                break;
+            }
+            n += 1;
+        }
+        n
+    }
+    fn step(&mut self, c: char) -> bool {
+        let cmd = match char_to_command(c) {
+            Some(cmd) if !self.is_over() => cmd,
+            _ => return false,
+        };
+        let s = self.state().apply(cmd);
+        self.states.push(s);
+        self.played.push(c);
+        true
+    }
+
+    pub fn undo(&mut self) -> bool {
+        match self.played.pop() {
+            Some(c) => {
+                self.states.pop();
+                self.undone.push(c);
+                true
+            },
+            None => false,
+        }
+    }
+    pub fn redo(&mut self) -> bool {
+        match self.undone.pop() {
+            Some(c) => self.step(c),
+            None => false,
+        }
+    }
+
+    pub fn move_score(&self) -> Score {
+        self.state().score
+    }
+    pub fn power_score(&self, phrases: &[String]) -> Score {
+        simulate::score_pop(&self.played, phrases)
+    }
+    pub fn to_solution(&self, tag: Option<String>) -> Solution {
+        Solution {
+            problem_id: self.problem_id,
+            seed: self.initial().seed,
+            tag,
+            solution: self.played.clone(),
+        }
+    }
+}
+
+/// Which key does which move.  The number pad keys 4/6/1/3 and 5/2
+/// always work as well.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    /// The keys for west, east, south-west, south-east, clockwise and
+    /// counter-clockwise.
+    keys: [char; 6],
+}
+
+const COMMANDS: [Command; 6] = [Command::Move(Direction::W), Command::Move(Direction::E),
+                                Command::Move(Direction::SW), Command::Move(Direction::SE),
+                                Command::Rotate(Clock::Wise), Command::Rotate(Clock::Counter)];
+const NUMPAD: [char; 6] = ['4', '6', '1', '3', '5', '2'];
+
+impl Keymap {
+    /// Keys a keymap may not use, because they do something else.
+    pub const RESERVED: &'static str = "urpwqo[]";
+
+    pub fn command(&self, key: char) -> Option<Command> {
+        self.keys.iter().position(|&k| k == key)
+            .or_else(|| NUMPAD.iter().position(|&k| k == key))
+            .map(|i| COMMANDS[i])
+    }
+    /// The key for `cmd`.
+    pub fn key(&self, cmd: Command) -> char {
+        self.keys[COMMANDS.iter().position(|&c| c == cmd).unwrap()]
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Keymap {
+        Keymap { keys: ['a', 'd', 'z', 'c', 's', 'x'] }
+    }
+}
+
+impl FromStr for Keymap {
+    type Err = String;
+    /// Six keys, in the order west, east, south-west, south-east,
+    /// clockwise and counter-clockwise, like "adzcsx".
+    fn from_str(s: &str) -> Result<Keymap, String> {
+        let keys: Vec<char> = s.chars().collect();
+        if keys.len() != 6 {
+            return Err(format!("a keymap is six keys, not \"{}\"", s));
+        }
+        for (i, &k) in keys.iter().enumerate() {
+            if Keymap::RESERVED.contains(k) || NUMPAD.contains(&k) || k.is_whitespace() {
+                return Err(format!("'{}' can't be in a keymap", k));
+            }
+            if keys[..i].contains(&k) {
+                return Err(format!("'{}' is in the keymap twice", k));
+            }
+        }
+        Ok(Keymap { keys: [keys[0], keys[1], keys[2], keys[3], keys[4], keys[5]] })
+    }
+}
+
+#[test]
+fn keymaps() {
+    let k = Keymap::default();
+    assert_eq!(k.command('a'), Some(Command::Move(Direction::W)));
+    assert_eq!(k.command('2'), Some(Command::Rotate(Clock::Counter)));
+    assert_eq!(k.command('u'), None);
+    let k: Keymap = "hlbnjk".parse().unwrap();
+    assert_eq!(k.command('n'), Some(Command::Move(Direction::SE)));
+    assert_eq!(k.command('4'), Some(Command::Move(Direction::W)));
+    assert_eq!(k.command('a'), None);
+    assert_eq!(k.key(Command::Rotate(Clock::Wise)), 'j');
+    assert!("hlbnj".parse::<Keymap>().is_err());
+    assert!("hlbnjj".parse::<Keymap>().is_err());
+    assert!("hlbnjq".parse::<Keymap>().is_err());
+    assert!("hlbnj5".parse::<Keymap>().is_err());
+}
+
+#[test]
+fn undo_and_redo() {
+    let initial = input_to_states(&Input::from_json("problems/problem_1.json"))[0].clone();
+    let mut g = Game::new(1, initial.clone());
+    assert!(g.play('l'));
+    assert!(!g.play('?'));
+    assert_eq!(g.play_str("ei!"), 3);
+    assert_eq!(g.play_str("e?"), 0);
+    assert_eq!(g.played(), "lei!");
+    assert_eq!(*g.state(), initial.apply_sequence(&string_to_commands("lei!")));
+    assert!(g.undo() && g.undo());
+    assert_eq!(g.played(), "le");
+    assert!(g.redo());
+    assert_eq!(g.played(), "lei");
+    // Playing something new forgets what was undone.
+    assert!(g.play('a'));
+    assert!(!g.redo());
+    while g.undo() {}
+    assert_eq!(*g.state(), initial);
+    assert_eq!(g.played(), "");
+
+    let sol = g.to_solution(None);
+    assert_eq!((sol.problem_id, sol.seed), (1, 0));
+    assert_eq!(g.power_score(&["ei!".into()]), 0);
+}
@@ -0,0 +1,258 @@
+//! A replay as one HTML page: the board after every move as data,
+//! and a little viewer to step through it, with the score over time
+//! and the phrases of power in the solution.  The page needs nothing
+//! else, so it can be attached to a ticket and opened anywhere.
+
+use super::*;
+use render::{Render, Style, LOOKS};
+
+use serde_json;
+use std::collections::BTreeMap;
+
+/// What the viewer gets to see.
+#[derive(Debug, Serialize, Deserialize)]
+struct Game {
+    #[serde(rename = "problemId")]
+    problem_id: i32,
+    seed: i32,
+    tag: Option<String>,
+    solution: String,
+    width: i32,
+    height: i32,
+    phrases: Vec<String>,
+    /// The board before each move and after the last one played, a
+    /// character a cell, as `render` draws them.
+    boards: Vec<String>,
+    #[serde(rename = "moveScores")]
+    move_scores: Vec<Score>,
+    #[serde(rename = "powerScores")]
+    power_scores: Vec<Score>,
+    /// The phrase (an index into `phrases`) each character is part of.
+    #[serde(rename = "phraseAt")]
+    phrase_at: Vec<Option<usize>>,
+    /// The color of each character in `boards`.
+    colors: BTreeMap<String, String>,
+}
+
+/// The page for `sol`, scoring phrases of power `phrases`.
+pub fn page(sol: &Solution, problems: &problems::Problems, phrases: &[String]) -> String {
+    let chars: Vec<char> = sol.solution.chars().collect();
+    let mut game = Game {
+        problem_id: sol.problem_id,
+        seed: sol.seed,
+        tag: sol.tag.clone(),
+        solution: sol.solution.clone(),
+        width: 0,
+        height: 0,
+        phrases: phrases.to_vec(),
+        boards: Vec::new(),
+        move_scores: Vec::new(),
+        power_scores: Vec::new(),
+        phrase_at: timeline::find_phrases(&chars, phrases),
+        colors: LOOKS.iter().map(|&l| (l.glyph(false).to_string(), export::hex_color(l))).collect(),
+    };
+    // Count each phrase as it is finished, which is how score_pop
+    // counts them, rather than scoring every prefix over again.
+    let lower: Vec<String> = phrases.iter().map(|p| p.chars().map(simulate::lower_case).collect()).collect();
+    let mut played = String::new();
+    let mut counts = vec![0; phrases.len()];
+    sol.replay(problems, |ch, state, rest| {
+        if let Some(ch) = ch {
+            played.push(simulate::lower_case(ch));
+            for (p, phrase) in lower.iter().enumerate() {
+                if !phrase.is_empty() && played.ends_with(&phrase[..]) {
+                    counts[p] += 1;
+                }
+            }
+        }
+        let mut r = Render::new(state, Style::default());
+        r.path(state, rest);
+        let mut board = String::with_capacity((state.width*state.height) as usize);
+        for y in 0 .. state.height {
+            for x in 0 .. state.width {
+                board.push(r.look(Cell::new(x, y)).glyph(false));
+            }
+        }
+        game.width = state.width;
+        game.height = state.height;
+        game.boards.push(board);
+        game.move_scores.push(state.score);
+        game.power_scores.push(counts.iter().zip(phrases.iter())
+                               .map(|(&n, p)| simulate::phrase_score(p, n)).sum());
+    });
+    let data = serde_json::to_string(&game).expect("Couldn't turn a game into JSON.");
+    let title = format!("Problem {}, seed {}", sol.problem_id, sol.seed);
+    // "</" would end the script the data is in.
+    PAGE.replace("TITLE", &title).replace("DATA", &data.replace("</", "<\\/"))
+}
+
+const PAGE: &str = r##"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>TITLE</title>
+<style>
+body { font-family: sans-serif; margin: 1em; }
+button { min-width: 3em; }
+#status { margin-left: 1em; }
+#letters { font-family: monospace; font-size: 14px; word-break: break-all; max-width: 60em; margin-top: 1em; }
+#letters span { cursor: pointer; }
+#letters .phrase { background: #f3e0a0; }
+#letters .next { background: #3070d0; color: white; }
+#letters .over { color: #aaa; }
+</style>
+</head>
+<body>
+<h2 id="title">TITLE</h2>
+<canvas id="board"></canvas>
+<div>
+<button id="start">|&lt;</button><button id="back">&lt;</button><button id="play">play</button><button id="forward">&gt;</button><button id="end">&gt;|</button>
+<input id="slider" type="range" min="0" value="0" style="width: 30em">
+<span id="status"></span>
+</div>
+<canvas id="chart" width="640" height="120"></canvas>
+<div id="letters"></div>
+<script type="application/json" id="game">DATA</script>
+<script>
+var g = JSON.parse(document.getElementById("game").textContent);
+var last = g.boards.length - 1, pos = 0, timer = null;
+var R = 10, W = Math.sqrt(3)*R, M = 4;
+var board = document.getElementById("board"), chart = document.getElementById("chart");
+var slider = document.getElementById("slider"), statusLine = document.getElementById("status");
+board.width = Math.ceil(2*M + W*(g.width + 0.5));
+board.height = Math.ceil(2*M + 2*R + 1.5*R*Math.max(0, g.height - 1));
+slider.max = last;
+if (g.tag) { document.getElementById("title").textContent += ": " + g.tag; }
+
+var spans = [];
+var letters = document.getElementById("letters");
+// One span a character, as phraseAt counts them, not a UTF-16 unit.
+var solution = Array.from(g.solution);
+for (var i = 0; i < solution.length; i++) {
+  var s = document.createElement("span");
+  s.textContent = solution[i];
+  if (g.phraseAt[i] !== null) { s.className = "phrase"; s.title = g.phrases[g.phraseAt[i]]; }
+  if (i >= last) { s.className += " over"; }
+  s.onclick = (function (i) { return function () { go(i); }; })(i);
+  letters.appendChild(s);
+  spans.push(s);
+}
+
+function hexagon(ctx, cx, cy, r) {
+  ctx.beginPath();
+  for (var i = 0; i < 6; i++) {
+    var a = Math.PI/3*i + Math.PI/6;
+    ctx.lineTo(cx + r*Math.cos(a), cy + r*Math.sin(a));
+  }
+  ctx.closePath();
+  ctx.fill();
+}
+
+function drawBoard() {
+  var ctx = board.getContext("2d"), b = g.boards[pos];
+  ctx.fillStyle = "#ffffff";
+  ctx.fillRect(0, 0, board.width, board.height);
+  for (var y = 0; y < g.height; y++) {
+    for (var x = 0; x < g.width; x++) {
+      ctx.fillStyle = g.colors[b[y*g.width + x]];
+      hexagon(ctx, M + W*(x + 0.5 + 0.5*(y % 2)), M + R + 1.5*R*y, 0.92*R);
+    }
+  }
+}
+
+function total(i) { return g.moveScores[i] + g.powerScores[i]; }
+
+function drawChart() {
+  var ctx = chart.getContext("2d"), w = chart.width, h = chart.height;
+  var top = Math.max(1, total(last));
+  ctx.clearRect(0, 0, w, h);
+  var line = function (scores, color) {
+    ctx.strokeStyle = color;
+    ctx.beginPath();
+    for (var i = 0; i <= last; i++) {
+      ctx.lineTo(w*i/Math.max(1, last), h - 2 - (h - 4)*scores(i)/top);
+    }
+    ctx.stroke();
+  };
+  line(function (i) { return g.moveScores[i]; }, "#2c4a8a");
+  line(total, "#c040c0");
+  ctx.strokeStyle = "#999999";
+  ctx.beginPath();
+  ctx.moveTo(w*pos/Math.max(1, last), 0);
+  ctx.lineTo(w*pos/Math.max(1, last), h);
+  ctx.stroke();
+}
+
+function draw() {
+  drawBoard();
+  drawChart();
+  slider.value = pos;
+  var text = "move " + pos + "/" + last + ": " + g.moveScores[pos] + " + " +
+      g.powerScores[pos] + " power = " + total(pos);
+  if (pos < solution.length && g.phraseAt[pos] !== null) {
+    text += ", next is part of \"" + g.phrases[g.phraseAt[pos]] + "\"";
+  }
+  if (last < solution.length) { text += " (the game is over after move " + last + ")"; }
+  statusLine.textContent = text;
+  for (var i = 0; i < spans.length; i++) {
+    spans[i].classList.toggle("next", i == pos);
+  }
+}
+
+function go(i) { pos = Math.max(0, Math.min(last, i)); draw(); }
+
+function play() {
+  if (timer) { clearInterval(timer); timer = null; }
+  else {
+    if (pos == last) { pos = 0; }
+    timer = setInterval(function () {
+      if (pos >= last) { play(); } else { go(pos + 1); }
+    }, 100);
+  }
+  document.getElementById("play").textContent = timer ? "pause" : "play";
+}
+
+document.getElementById("start").onclick = function () { go(0); };
+document.getElementById("back").onclick = function () { go(pos - 1); };
+document.getElementById("forward").onclick = function () { go(pos + 1); };
+document.getElementById("end").onclick = function () { go(last); };
+document.getElementById("play").onclick = play;
+slider.oninput = function () { go(parseInt(slider.value)); };
+document.onkeydown = function (e) {
+  if (e.key == "ArrowRight") { go(pos + 1); }
+  else if (e.key == "ArrowLeft") { go(pos - 1); }
+  else if (e.key == "Home") { go(0); }
+  else if (e.key == "End") { go(last); }
+  else if (e.key == " ") { play(); e.preventDefault(); }
+};
+draw();
+</script>
+</body>
+</html>
+"##;
+
+#[test]
+fn page_holds_the_game() {
+    let ps = problems::Problems::from_files(&["problems/problem_1.json"]).unwrap();
+    let sol = Solution { problem_id: 1, seed: 0, tag: Some("</script>".into()), solution: "ei!lllaaaal".into() };
+    let phrases = vec!["ei!".to_string(), "aa".to_string()];
+    let html = page(&sol, &ps, &phrases);
+    assert!(!html.contains("DATA"));
+    // Our tag must not close the script early.
+    assert_eq!(html.matches("</script>").count(), 2);
+
+    let start = html.find("id=\"game\">").unwrap() + "id=\"game\">".len();
+    let end = start + html[start..].find("</script>").unwrap();
+    let game: Game = serde_json::from_str(&html[start..end]).unwrap();
+    assert_eq!(game.boards.len(), sol.solution.len() + 1);
+    assert!(game.boards.iter().all(|b| b.len() == (game.width*game.height) as usize));
+    let initial = ps.initial_state(1, 0).unwrap();
+    let end = simulate::score_commands(&string_to_commands(&sol.solution), &initial);
+    assert_eq!(*game.move_scores.last().unwrap(), end.score);
+    for i in 0 .. game.power_scores.len() {
+        assert_eq!(game.power_scores[i], simulate::score_pop(&sol.solution[..i], &phrases));
+    }
+    assert_eq!(game.phrase_at[1], Some(0));
+    assert_eq!(game.phrase_at[7], Some(1));
+    assert!(game.colors.contains_key("X"));
+}
@@ -1,24 +1,29 @@
 use super::*;
 
-use rustc_serialize::json;
+use serde_json;
 use std::path::Path;
 use std::fs::File;
 use std::str;
-use std::io::Read;
+use std::io::{self, Read};
 
 use std::convert::AsRef;
 
+fn read_file<P: AsRef<Path>>(fname: P) -> String {
+    let mut temp = String::new();
+    let mut file = match File::open(fname) {
+        Ok(r) => r,
+        Err(e) => panic!("Failed to open file with error {}", e),
+    };
+    file.read_to_string(&mut temp).expect("Failed to read file contents.");
+    temp
+}
+
 impl Input {
     pub fn from_json<P: AsRef<Path>>(fname: P) -> Input {
-        let mut temp = String::new();
-        let mut file = match File::open(fname) {
-            Ok(r) => r,
-            Err(e) => panic!("Failed to open file with error {}", e),
-        };
-        file.read_to_string(&mut temp).ok().expect("Failed to read file contents.");
-        let input: &str = str::from_utf8(temp.as_bytes()).ok().expect("Failed to convert &[u8] to &str???");
+        let temp = read_file(fname);
+        let input: &str = str::from_utf8(temp.as_bytes()).expect("Failed to convert &[u8] to &str???");
 
-        let decoded: Input = match json::decode(input) {
+        let decoded: Input = match serde_json::from_str(input) {
             Ok(r) => r,
             Err(e) => panic!("Failed to decode JSON with error: {}", e),
         };
@@ -38,56 +43,90 @@ fn decode_test() {
         width: 5,
         height: 5,
         filled: vec![Cell{x: 2, y: 4}],
-        sourceLength: 100,
-        sourceSeeds: vec![0],
+        source_length: 100,
+        source_seeds: vec![0],
     };
-    let from_file = Input::from_json("problems/test.json");
+    let from_file = Input::from_json("tests/data/test.json");
 
     assert_eq!(manual, from_file);
 
 }
 
 impl Solution {
+    /// Read a file holding a single solution.  See `load_solutions`
+    /// for files like the ones we submit.
+    pub fn from_json<P: AsRef<Path>>(fname: P) -> io::Result<Solution> {
+        let mut contents = String::new();
+        File::open(fname)?.read_to_string(&mut contents)?;
+        let sol: Solution = serde_json::from_str(&contents).map_err(invalid)?;
+        sol.check()?;
+        Ok(sol)
+    }
+    /// Make sure every letter of the solution is a command, since
+    /// nothing that plays it checks.
+    pub fn check(&self) -> io::Result<()> {
+        match bad_command(&self.solution) {
+            Some((i, c)) => Err(invalid(format!("the solution for problem {}, seed {} has {:?} at {}, which is not a command",
+                                                self.problem_id, self.seed, c, i))),
+            None => Ok(()),
+        }
+    }
     pub fn to_json(&self) -> String {
-        json::encode(&self).ok().expect("Couldn't turn solution into JSON for some unknowable reason.")
+        serde_json::to_string(&self).expect("Couldn't turn solution into JSON for some unknowable reason.")
     }
-    pub fn animate(&self, sleep_in_ms: u32) {
-        use std::thread::sleep_ms;
-
-        let states = input_to_states(&Input::from_json(format!("problems/problem_{}.json", self.problemId)));
-        let mut state: State = states.iter().filter(|&s| s.seed == self.seed).next().expect("Solution has invalid seed").clone();
-        println!("{}[2J", 27 as char);
-        println!("Problem {}, seed {}:", self.problemId, self.seed);
-        println!("{}", state.visualize());
-        println!("Score: {}", state.score);
-
-        for ch in self.solution.chars() {
-            let cmd = string_to_commands(&format!("{}", ch));
-            sleep_ms(sleep_in_ms);
-            if sleep_in_ms != 0 {
-                println!("{}[2J", 27 as char);
-            }
-            println!("Problem {}, seed {}, move {}, cmd {:?}:",
-                     self.problemId, self.seed, ch, cmd);
-            state = state.apply_sequence(&cmd);
-            println!("{}", state.visualize());
-            println!("Score: {}", state.score);
+    /// Play the solution a character at a time, calling `frame`
+    /// with the character just played (`None` to begin with), the
+    /// state after it and what is left to play, until the game ends.
+    pub fn replay<F: FnMut(Option<char>, &State, &str)>(&self, problems: &problems::Problems, mut frame: F) {
+        let mut state = match problems.initial_state(self.problem_id, self.seed) {
+            Some(s) => s,
+            None => panic!("No problem {} with seed {} to replay", self.problem_id, self.seed),
+        };
+        frame(None, &state, &self.solution);
+        for (i, ch) in self.solution.char_indices() {
+            state = state.apply_sequence(&string_to_commands(&format!("{}", ch)));
+            frame(Some(ch), &state, &self.solution[i + ch.len_utf8()..]);
             if state.game_over { break; }
         }
     }
+    /// Show the solution being played, with the path each piece is
+    /// about to take.
+    pub fn animate(&self, problems: &problems::Problems, sleep_in_ms: u32, style: render::Style) {
+        use std::thread::sleep;
+        use std::time::Duration;
+
+        self.replay(problems, |ch, state, rest| {
+            match ch {
+                None => {
+                    println!("{}[2J", 27 as char);
+                    println!("Problem {}, seed {}:", self.problem_id, self.seed);
+                },
+                Some(ch) => {
+                    sleep(Duration::from_millis(sleep_in_ms as u64));
+                    if sleep_in_ms != 0 {
+                        println!("{}[2J", 27 as char);
+                    }
+                    println!("Problem {}, seed {}, move {}, cmd {:?}:",
+                             self.problem_id, self.seed, ch, string_to_commands(&format!("{}", ch)));
+                },
+            }
+            println!("{}", render::Render::new(state, style).path(state, rest));
+            println!("Score: {}", state.score);
+        });
+    }
 
 }
 
 #[test]
 fn encode_test() {
     let with_tag = Solution {
-        problemId: 5,
+        problem_id: 5,
         seed: 34,
         tag: Some("tagg".into()),
         solution: "cthulu".into()
     };
     let without_tag = Solution {
-        problemId: 5,
+        problem_id: 5,
         seed: 34,
         tag: None,
         solution: "cthulu".into()
@@ -95,34 +134,141 @@ fn encode_test() {
     println!("With tag: {}\n Without tag: {}.", with_tag.to_json(), without_tag.to_json());
 }
 
-pub fn submit_solutions(s: &Vec<Solution>) {
-    use std::process;
-    // println!("{}", json::encode(s).unwrap());
-    process::Command::new("curl")
-        .arg("--user").arg(":FtpwGAy9ndcLXLUlH7i96rgXLgi2SzEdym2caXEsNUI=")
-        .arg("-X").arg("POST")
-        .arg("-H").arg("Content-Type: application/json")
-        .arg("-d").arg(json::encode(s).unwrap())
-        .arg("https://davar.icfpcontest.org/teams/97/solutions")
-        .spawn().unwrap().wait().unwrap();
+/// Read a file of solutions, either an array of them like we submit
+/// and save, or just one.
+pub fn load_solutions<P: AsRef<Path>>(fname: P) -> io::Result<Vec<Solution>> {
+    let mut contents = String::new();
+    File::open(fname)?.read_to_string(&mut contents)?;
+    let sols: Vec<Solution> = match serde_json::from_str(&contents) {
+        Ok(r) => r,
+        Err(_) => vec![serde_json::from_str(&contents).map_err(invalid)?],
+    };
+    for sol in sols.iter() {
+        sol.check()?;
+    }
+    Ok(sols)
+}
+
+fn invalid<E: ToString>(e: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
+
+#[test]
+fn load_solutions_test() {
+    let sols = load_solutions("solutions/0-0-6811.json").unwrap();
+    assert_eq!(sols.len(), 1);
+    assert_eq!(sols[0].problem_id, 0);
+    assert_eq!(sols[0].seed, 0);
+    assert_eq!(sols[0].tag, Some("bottomupdfs[0,0] = 6811".into()));
+
+    let fname = ::std::env::temp_dir().join(format!("davar-solution-test-{}", ::std::process::id()));
+    {
+        use std::io::Write;
+        let mut f = File::create(&fname).unwrap();
+        f.write_all(sols[0].to_json().as_bytes()).unwrap();
+    }
+    assert_eq!(Solution::from_json(&fname).unwrap(), sols[0]);
+    assert_eq!(load_solutions(&fname).unwrap(), sols);
+
+    // A letter that isn't a command is an error, not a panic later.
+    {
+        use std::io::Write;
+        let mut bad = sols[0].clone();
+        bad.solution = "ei!?".into();
+        let mut f = File::create(&fname).unwrap();
+        f.write_all(serde_json::to_string(&vec![bad]).unwrap().as_bytes()).unwrap();
+    }
+    let e = load_solutions(&fname).unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+    assert!(e.to_string().contains("'?' at 3"));
+    assert!(Solution::from_json(&fname).is_err());
+    ::std::fs::remove_file(&fname).unwrap();
+}
+
+/// Read phrases of power, one per line, like the `pop` file.
+pub fn read_phrases<P: AsRef<Path>>(fname: P) -> ::std::io::Result<Vec<String>> {
+    let mut contents = String::new();
+    File::open(fname)?.read_to_string(&mut contents)?;
+    Ok(contents.lines().filter(|l| !l.is_empty()).map(|l| l.into()).collect())
 }
 
 pub fn print_solutions(s: &Vec<Solution>) {
-    println!("{}", json::encode(s).unwrap())
+    println!("{}", serde_json::to_string(s).unwrap())
 }
 
-pub fn save_solutions(s: &Vec<(Solution, Score)>) {
+/// One line of `--output jsonl`: the solution as we would submit
+/// it, with its score and how many seconds into the run we found it.
+#[derive(Serialize)]
+struct Progress<'a> {
+    #[serde(flatten)]
+    solution: &'a Solution,
+    score: Score,
+    elapsed: f64,
+}
+
+pub fn progress_line(s: &Solution, score: Score, elapsed: f64) -> String {
+    serde_json::to_string(&Progress { solution: s, score, elapsed }).unwrap()
+}
+
+/// Print one line of `--output jsonl`.  `println!` holds the lock on
+/// stdout for the whole line, and flushes it, so lines from different
+/// threads never interleave and are seen right away.
+pub fn print_progress(s: &Solution, score: Score, elapsed: f64) {
+    println!("{}", progress_line(s, score, elapsed));
+}
+
+#[test]
+fn progress_line_test() {
+    let sol = Solution { problem_id: 3, seed: 7, tag: Some("mc".into()), solution: "ei!".into() };
+    let line: serde_json::Value = serde_json::from_str(&progress_line(&sol, 42, 1.5)).unwrap();
+    assert_eq!(line["problemId"], 3);
+    assert_eq!(line["seed"], 7);
+    assert_eq!(line["tag"], "mc");
+    assert_eq!(line["solution"], "ei!");
+    assert_eq!(line["score"], 42);
+    assert_eq!(line["elapsed"], 1.5);
+    // The rest is just a solution, so the line can be submitted as is.
+    let again: Solution = serde_json::from_value(line).unwrap();
+    assert_eq!(again, sol);
+}
+
+pub fn save_solutions(s: &[(Solution, Score)]) {
     use std::fs::File;
     use std::io::Write;
-    for i in 0 .. s.len() {
+    for (sol, score) in s {
         let fname = format!("solutions/{}-{}-{}.json",
-                            s[i].0.problemId, s[i].0.seed, s[i].1);
+                            sol.problem_id, sol.seed, score);
         match File::create(&fname) {
             Err(..) => (),
             Ok(mut f) => {
-                let sol = s[i].0.clone();
-                f.write_all(&json::encode(&[sol]).unwrap().into_bytes()).ok().expect("Couldn't save :(");
+                f.write_all(&serde_json::to_string(&[sol]).unwrap().into_bytes()).expect("Couldn't save :(");
             }
         }
     }
 }
+
+/// Every problem and solution we have must come back unchanged after
+/// a trip through our types, so that we read and write exactly what
+/// the contest server does.
+#[test]
+fn json_round_trip() {
+    use serde_json::Value;
+
+    let mut n = 0;
+    for dir in ["problems", "solutions"].iter() {
+        for f in ::std::fs::read_dir(dir).unwrap() {
+            let path = f.unwrap().path();
+            let contents = read_file(&path);
+            let original: Value = serde_json::from_str(&contents).unwrap();
+            let again: Value = if *dir == "problems" {
+                let input: Input = serde_json::from_str(&contents).unwrap();
+                serde_json::to_value(&input).unwrap()
+            } else {
+                serde_json::to_value(load_solutions(&path).unwrap()).unwrap()
+            };
+            assert_eq!(original, again, "{} changed", path.display());
+            n += 1;
+        }
+    }
+    assert!(n > 0);
+}
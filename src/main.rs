@@ -1,92 +1,322 @@
 extern crate davar;
-extern crate rustc_serialize;
+extern crate ctrlc;
 
 use davar::*;
 // use davar::Direction::*;
 // use davar::Command::*;
-// use rustc_serialize::json;
-// use std::process;
+// use serde_json;
+use std::collections::HashMap;
+use std::env;
+use std::path::Path;
+use std::process;
+use std::sync::Arc;
 use std::thread;
+use std::time::Duration;
 use davar::opts::DavarOptions;
 
 #[allow(dead_code)]
 fn main() {
     let options = opts::opts();
-    let mut totalscore = 0;
-    let solver = solver::name_to_solver(&options.solver);
-    let mut fnames: Vec<String> = Vec::new();
-
-    if options.files.len() == 0 {
-        for i in 0..25 {
-            fnames.push(format!("problems/problem_{}.json", i)); // Fix this to work with any file name inputs...
-        }
+    memory::global().set_limit_mb(options.memory_limit);
+    ctrlc::set_handler(anytime::request_stop).expect("Couldn't set signal handler.");
+    if let Some(ref fname) = options.replay {
+        replay(&options, &load_problems(&options), fname);
+        return;
     }
-    else {
-        fnames = options.files.clone(); // See above comment
+    if let Some(ref dir) = options.export {
+        export_boards(&options, &load_problems(&options), dir);
+        return;
     }
-    let mut joinhandles: Vec<thread::JoinHandle<Vec<(Solution, Score)>>> = Vec::new();
-    {
-        let mut inputlists: Vec<Vec<(State, Input, DavarOptions)>> = Vec::new();
-        for _ in 0 .. options.ncores {
-            inputlists.push(Vec::new());
-        }
-        let mut which_core = 0;
-        for e in fnames.iter() {
-            let input = Input::from_json(e);
-            let states = input_to_states(&input);
-            for state in states {
-                inputlists[which_core].push((state, input.clone(), options.clone()));
-                which_core = (which_core + 1) % options.ncores;
-                //joinhandles.push(thread::spawn(move || { solver.solve(&state, &input, &options) }));
-            }
-        }
-        for _ in 0 .. options.ncores {
-            let myinput = match inputlists.pop() {
-                Some(inp) => inp,
-                _ => Vec::new(),
-            };
-            joinhandles.push(thread::spawn(move || { solver.solve_n(&myinput) }));
+    if let Some(ref format) = options.report {
+        report(&options, &load_problems(&options), format);
+        return;
+    }
+    if options.verify {
+        verify(&options, &load_problems(&options));
+        return;
+    }
+    if let Some(ref fname) = options.export_best {
+        let a = archive::Archive::open(&options.archive).expect("Couldn't open the archive.");
+        a.export_best(fname).expect("Couldn't write the best solutions.");
+        if options.verbose {
+            println!("Exported {} solutions to {}.", a.best().len(), fname);
         }
+        return;
     }
-    let mut solutions: Vec<Solution> = Vec::new();
-    let mut solutions_and_scores: Vec<(Solution, Score)> = Vec::new();
-    for jh in joinhandles {
-        match jh.join() {
+    if let Some(ref fname) = options.trace {
+        trace::global().open(fname).expect("Couldn't create the trace file.");
+    }
+    // Find the API token now, so we don't solve for an hour only to
+    // find out we can't submit.
+    let target = options.submit_to.clone().unwrap_or(submit::default_target());
+    let token = if options.submit {
+        match credentials::load(options.credentials.as_ref().map(Path::new)) {
+            Ok(c) => Some(c.token),
             Err(e) => {
-                if options.verbose {
-                    println!("Error! {:?}", e);
+                if submit::needs_credentials(&target) && !options.dry_run {
+                    println!("Refusing to --submit: {}", e);
+                    process::exit(1);
                 }
-            }
-            Ok(more_solutions) => {
-                for (s, sc) in more_solutions {
-                    solutions_and_scores.push((s.clone(), sc));
-                    solutions.push(s);
-                    totalscore += sc;
+                None
+            },
+        }
+    } else {
+        None
+    };
+    let mut totalscore = 0;
+    let solver: Arc<Box<dyn solver::Solve>> = match solver::configure(&options) {
+        Ok(s) => Arc::new(s),
+        Err(e) => {
+            println!("{}", e);
+            process::exit(1);
+        },
+    };
+    let problems = load_problems(&options);
+    let jobs: Vec<schedule::Job> = match problems.select(options.problem.as_ref(), options.seed.as_ref()) {
+        Ok(chosen) => chosen.into_iter().map(|(input, state)| schedule::Job::new(state, input.clone())).collect(),
+        Err(e) => {
+            println!("{}", e);
+            process::exit(1);
+        },
+    };
+    let scheduler = Arc::new(schedule::Scheduler::new(jobs, &options));
+    let mut joinhandles: Vec<thread::JoinHandle<()>> = Vec::new();
+    for _ in 0 .. options.ncores {
+        let scheduler = scheduler.clone();
+        let solver = solver.clone();
+        joinhandles.push(thread::spawn(move || { scheduler.run(&**solver) }));
+    }
+    // Wait for the solvers, but once time is up (or we are told to
+    // stop) just go with the best solutions published so far.  We
+    // leave half the scheduler's buffer for printing and saving.
+    while !joinhandles.is_empty() && !anytime::stop_requested()
+        && options.time_left() > schedule::BUFFER_TIME/2.0 {
+        thread::sleep(Duration::from_millis(20));
+        let (done, running): (Vec<_>, Vec<_>) = joinhandles.into_iter().partition(|jh| jh.is_finished());
+        joinhandles = running;
+        for jh in done {
+            if let Err(e) = jh.join() {
+                if options.verbose {
+                    eprintln!("Error! {:?}", e);
                 }
             }
         }
     }
+    if options.verbose && !joinhandles.is_empty() {
+        eprintln!("Stopping with {} solver threads still running.", joinhandles.len());
+    }
+    let mut solutions: Vec<Solution> = Vec::new();
+    let mut solutions_and_scores: Vec<(Solution, Score)> = Vec::new();
+    for (s, sc) in anytime::global().snapshot() {
+        solutions_and_scores.push((s.clone(), sc));
+        solutions.push(s);
+        totalscore += sc;
+    }
     if options.submit {
-        in_out::submit_solutions(&solutions);
+        let submitter: Result<Box<dyn submit::Submitter>, _> = if options.dry_run {
+            Ok(Box::new(submit::DryRun { url: target, token }))
+        } else {
+            submit::from_target(&target, token)
+        };
+        match submitter.and_then(|s| s.submit(&solutions)) {
+            Ok(r) => eprintln!("Submitted {} solutions, {}", solutions.len(), r),
+            Err(e) => eprintln!("{}", e),
+        }
     }
-    if !options.verbose {
+    if options.output == "jsonl" {
+        // Anything solvers published without finishing their job.
+        for &(ref s, sc) in solutions_and_scores.iter() {
+            scheduler.stream(s, sc);
+        }
+    } else if !options.verbose {
         in_out::print_solutions(&solutions);
     }
     if options.save_solutions {
         in_out::save_solutions(&solutions_and_scores);
+        let args: Vec<String> = env::args().skip(1).collect();
+        let mut a = archive::Archive::open(&options.archive).expect("Couldn't open the archive.");
+        for &(ref s, sc) in solutions_and_scores.iter() {
+            let initial = problems.initial_state(s.problem_id, s.seed)
+                .expect("Solved a seed we don't have a problem for?!");
+            a.add(archive::Entry::new(s, sc, &options.solver, &args,
+                                      &options.phrases_of_power, &initial))
+                .expect("Couldn't save to the archive.");
+        }
     }
 
     if let Some(a) = options.animate {
         for s in solutions {
-            s.animate(a);
+            s.animate(&problems, a, options.style);
         }
     }
 
+    // Solvers still running past the deadline have nothing to add.
+    trace::global().close().expect("Couldn't finish writing the trace.");
     if options.verbose {
-        println!("total score: {}", totalscore);
+        eprintln!("total score: {}", totalscore);
+        eprintln!("peak search memory: {:.1} MB", memory::global().peak() as f64/(1024.0*1024.0));
     }
 
     if !options.submit && options.verbose {
-        println!("Not submitting solutions.");
+        eprintln!("Not submitting solutions.");
+    }
+}
+
+/// The problems given with -f, or else all of those in the problems
+/// directory.
+fn load_problems(options: &DavarOptions) -> problems::Problems {
+    let ps = if !options.files.is_empty() {
+        problems::Problems::from_files(&options.files)
+    } else {
+        problems::Problems::from_dir(&options.problems)
+    };
+    match ps {
+        Ok(ps) => ps,
+        Err(e) => {
+            println!("{}", e);
+            process::exit(1);
+        },
+    }
+}
+
+/// Replay everything in the archive and report anything fishy.
+fn verify(options: &DavarOptions, problems: &problems::Problems) {
+    use davar::simulate::Ending;
+
+    let mut a = archive::Archive::open(&options.archive).expect("Couldn't open the archive.");
+    let mut states: HashMap<i32, Vec<State>> = HashMap::new();
+    let results = a.verify(|problem_id, seed| {
+        let ss = states.entry(problem_id).or_insert_with(|| {
+            problems.get(problem_id).map(input_to_states).unwrap_or_default()
+        });
+        ss.iter().find(|s| s.seed == seed).cloned()
+    });
+    let results = match results {
+        Ok(r) => r,
+        Err(e) => {
+            println!("Couldn't verify the archive: {}", e);
+            process::exit(1);
+        },
+    };
+
+    let mut nsuspect = 0;
+    for v in results.iter().filter(|v| v.is_suspect()) {
+        let e = &v.entry;
+        let problem = match v.ending {
+            Ending::Clean => String::new(),
+            Ending::Revisit(i) => format!(", revisits a position at command {}", i),
+            Ending::Trailing(i) => format!(", {} unused commands from command {}",
+                                           string_to_commands(&e.solution).len() - i, i),
+        };
+        println!("{}-{} {}: claimed {}, was {}, now {} (move {} + power {}){}",
+                 e.problem_id, e.seed, e.solver, e.claimed_score, v.old_total,
+                 e.total_score, e.move_score, e.power_score, problem);
+        nsuspect += 1;
+    }
+    println!("Verified {} solutions, {} need a look.", results.len(), nsuspect);
+}
+
+/// Rescore the solutions in a saved file with the current simulator,
+/// and animate or export them if asked to.
+fn replay(options: &DavarOptions, problems: &problems::Problems, fname: &str) {
+    use davar::simulate::{self, Ending};
+
+    let solutions = match in_out::load_solutions(fname) {
+        Ok(sols) => sols,
+        Err(e) => {
+            println!("Couldn't read {}: {}", fname, e);
+            process::exit(1);
+        },
+    };
+    for sol in solutions {
+        let initial = match problems.initial_state(sol.problem_id, sol.seed) {
+            Some(s) => s,
+            None => {
+                println!("{}-{}: we have no problem {} with seed {}", sol.problem_id, sol.seed,
+                         sol.problem_id, sol.seed);
+                continue;
+            },
+        };
+        let (s, ending) = simulate::replay(&string_to_commands(&sol.solution), &initial);
+        let power = if ending == Ending::Clean {
+            simulate::score_pop(&sol.solution, &options.phrases_of_power)
+        } else {
+            0
+        };
+        println!("{}-{}: move {} + power {} = {}{}", sol.problem_id, sol.seed,
+                 s.score, power, s.score + power,
+                 match ending {
+                     Ending::Clean => String::new(),
+                     Ending::Revisit(i) => format!(" (revisits a position at command {})", i),
+                     Ending::Trailing(i) => format!(" (game over before command {})", i),
+                 });
+        if let Some(a) = options.animate {
+            sol.animate(problems, a, options.style);
+        }
+        if let Some(ref dir) = options.export {
+            // GIF frames as far apart as the animation's, or 100 ms.
+            let delay = options.animate.filter(|&a| a > 0).unwrap_or(100);
+            match export::export(&sol, problems, &options.phrases_of_power, Path::new(dir),
+                                 options.export_format, delay) {
+                Ok(path) => println!("{}-{}: wrote {}", sol.problem_id, sol.seed, path.display()),
+                Err(e) => println!("{}-{}: couldn't export: {}", sol.problem_id, sol.seed, e),
+            }
+        }
+    }
+}
+
+/// Write pictures of the starting boards of the chosen problems and
+/// seeds.
+fn export_boards(options: &DavarOptions, problems: &problems::Problems, dir: &str) {
+    let chosen = match problems.select(options.problem.as_ref(), options.seed.as_ref()) {
+        Ok(chosen) => chosen,
+        Err(e) => {
+            println!("{}", e);
+            process::exit(1);
+        },
+    };
+    for (input, state) in chosen {
+        match export::export_board(&state, input.id, Path::new(dir), options.export_format) {
+            Ok(path) => println!("{}-{}: wrote {}", input.id, state.seed, path.display()),
+            Err(e) => println!("{}-{}: couldn't export: {}", input.id, state.seed, e),
+        }
+    }
+}
+
+/// Print a contest-style report of the archive's best solutions, or
+/// of the server's results.
+fn report(options: &DavarOptions, problems: &problems::Problems, format: &str) {
+    let (results, phrases) = match options.results {
+        Some(ref fname) => (report::server_results(fname, &options.phrases_of_power),
+                            options.phrases_of_power.clone()),
+        None => {
+            let a = archive::Archive::open(&options.archive).expect("Couldn't open the archive.");
+            let best = a.best();
+            // Count the phrases we were asked about, or else all the
+            // ones the solutions were made with.
+            let mut phrases = options.phrases_of_power.clone();
+            if phrases.is_empty() {
+                for e in best.iter() {
+                    for p in e.phrases.iter() {
+                        if !phrases.contains(p) {
+                            phrases.push(p.clone());
+                        }
+                    }
+                }
+            }
+            (best.iter().map(|e| (e.problem_id, report::SeedResult::from_entry(e, &phrases))).collect(),
+             phrases)
+        },
+    };
+    let r = report::Report::new(results, &phrases, |problem_id| {
+        problems.get(problem_id).map(|p| p.source_seeds.clone()).unwrap_or_default()
+    });
+    match format {
+        "json" => println!("{}", r.to_json()),
+        "table" => print!("{}", r.to_table()),
+        _ => {
+            println!("Unknown report format \"{}\" (try table or json)", format);
+            process::exit(1);
+        },
     }
 }
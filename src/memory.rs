@@ -0,0 +1,237 @@
+//! Accounting for the `-m` memory limit.
+//!
+//! The contest treats the memory limit as hard, so the search
+//! structures in the solvers (DFS stacks and visited lists, Monte
+//! Carlo playouts, the resting positions we try) reserve an estimate
+//! of their size here before growing.  When a reservation is refused,
+//! the solver prunes that branch or stops expanding.  Only a fraction
+//! of the limit is handed out to the searches, since the problems,
+//! threads and output need room too.
+
+use super::*;
+
+use std::mem::size_of;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Percentage of the `-m` limit that search structures may use.
+const SEARCH_FRACTION: usize = 75;
+
+pub struct MemoryBudget {
+    limit: AtomicUsize, // in bytes, zero means unlimited
+    in_use: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+static GLOBAL: MemoryBudget = MemoryBudget::unlimited();
+
+/// The budget shared by every solver thread.
+pub fn global() -> &'static MemoryBudget {
+    &GLOBAL
+}
+
+impl MemoryBudget {
+    pub const fn unlimited() -> MemoryBudget {
+        MemoryBudget {
+            limit: AtomicUsize::new(0),
+            in_use: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+        }
+    }
+    /// Set the limit from the `-m` option, which is in megabytes.
+    pub fn set_limit_mb(&self, megabytes: Option<usize>) {
+        let bytes = match megabytes {
+            Some(mb) => ::std::cmp::max(1, mb.saturating_mul(1024*1024)/100*SEARCH_FRACTION),
+            None => 0,
+        };
+        self.limit.store(bytes, Ordering::SeqCst);
+    }
+    pub fn limit(&self) -> Option<usize> {
+        match self.limit.load(Ordering::SeqCst) {
+            0 => None,
+            l => Some(l),
+        }
+    }
+    /// Try to reserve `bytes`.  Returns false (and reserves nothing)
+    /// if that would take us over the limit.
+    pub fn try_reserve(&self, bytes: usize) -> bool {
+        let limit = self.limit.load(Ordering::SeqCst);
+        let mut current = self.in_use.load(Ordering::SeqCst);
+        loop {
+            let wanted = current + bytes;
+            if limit != 0 && wanted > limit {
+                return false;
+            }
+            match self.in_use.compare_exchange(current, wanted,
+                                               Ordering::SeqCst, Ordering::SeqCst) {
+                Ok(_) => {
+                    self.peak.fetch_max(wanted, Ordering::SeqCst);
+                    return true;
+                },
+                Err(actual) => current = actual,
+            }
+        }
+    }
+    pub fn release(&self, bytes: usize) {
+        self.in_use.fetch_sub(bytes, Ordering::SeqCst);
+    }
+    pub fn in_use(&self) -> usize {
+        self.in_use.load(Ordering::SeqCst)
+    }
+    /// The most memory that was ever reserved at once.
+    pub fn peak(&self) -> usize {
+        self.peak.load(Ordering::SeqCst)
+    }
+    /// True when more than 90% of the limit is reserved, so solvers
+    /// can stop starting new expensive work.
+    pub fn near_limit(&self) -> bool {
+        match self.limit() {
+            None => false,
+            Some(l) => self.in_use() > l/10*9,
+        }
+    }
+}
+
+/// Memory reserved from a `MemoryBudget`, released when dropped.
+pub struct Reservation<'a> {
+    budget: &'a MemoryBudget,
+    bytes: usize,
+}
+
+impl<'a> Reservation<'a> {
+    /// Nothing reserved yet.
+    pub fn new(budget: &'a MemoryBudget) -> Reservation<'a> {
+        Reservation { budget, bytes: 0 }
+    }
+    /// Reserve `bytes` more, unless the budget refuses.
+    pub fn grow(&mut self, bytes: usize) -> bool {
+        if self.budget.try_reserve(bytes) {
+            self.bytes += bytes;
+            true
+        } else {
+            false
+        }
+    }
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+}
+
+impl<'a> Drop for Reservation<'a> {
+    fn drop(&mut self) {
+        self.budget.release(self.bytes);
+    }
+}
+
+/// A stack whose entries are charged against a `MemoryBudget`.
+/// Everything still on the stack is released when it is dropped.
+pub struct TrackedStack<'a, T> {
+    budget: &'a MemoryBudget,
+    items: Vec<(T, usize)>,
+}
+
+impl<'a, T> TrackedStack<'a, T> {
+    pub fn new(budget: &'a MemoryBudget) -> TrackedStack<'a, T> {
+        TrackedStack { budget, items: Vec::new() }
+    }
+    /// Push `item`, which is estimated to use `bytes`.  If the budget
+    /// refuses, the item is handed back.
+    pub fn push(&mut self, item: T, bytes: usize) -> Result<(), T> {
+        if self.budget.try_reserve(bytes) {
+            self.items.push((item, bytes));
+            Ok(())
+        } else {
+            Err(item)
+        }
+    }
+    pub fn pop(&mut self) -> Option<T> {
+        match self.items.pop() {
+            Some((item, bytes)) => {
+                self.budget.release(bytes);
+                Some(item)
+            },
+            None => None,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+    pub fn contains(&self, item: &T) -> bool where T: PartialEq {
+        self.items.iter().any(|x| x.0 == *item)
+    }
+}
+
+impl<'a, T> Drop for TrackedStack<'a, T> {
+    fn drop(&mut self) {
+        let total = self.items.iter().fold(0, |acc, x| acc + x.1);
+        self.budget.release(total);
+    }
+}
+
+impl Unit {
+    pub fn approx_bytes(&self) -> usize {
+        size_of::<Unit>() + self.members.capacity()*size_of::<Cell>()
+    }
+}
+
+impl State {
+    /// An estimate of the memory held by this state, including its
+    /// heap allocations.  This walks every unit, so searches work it
+    /// out once rather than for every state they keep.
+    pub fn approx_bytes(&self) -> usize {
+        size_of::<State>()
+            + self.filled_array.capacity()*size_of::<bool>()
+            + self.visited.iter().fold(0, |acc, u| acc + u.approx_bytes())
+            + self.unit_sequence.iter().fold(0, |acc, u| acc + u.approx_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refuses_past_limit() {
+        let b = MemoryBudget::unlimited();
+        b.set_limit_mb(Some(1));
+        let limit = b.limit().unwrap();
+        assert!(b.try_reserve(limit/2));
+        assert!(!b.try_reserve(limit));
+        assert_eq!(b.in_use(), limit/2);
+        b.release(limit/2);
+        assert_eq!(b.in_use(), 0);
+        assert_eq!(b.peak(), limit/2);
+    }
+
+    #[test]
+    fn stack_releases_on_drop() {
+        let b = MemoryBudget::unlimited();
+        b.set_limit_mb(Some(1));
+        {
+            let mut stack = TrackedStack::new(&b);
+            assert!(stack.push(1, 1000).is_ok());
+            assert!(stack.push(2, 2000).is_ok());
+            assert_eq!(stack.pop(), Some(2));
+            assert!(stack.push(3, b.limit().unwrap()).is_err());
+            assert_eq!(b.in_use(), 1000);
+        }
+        assert_eq!(b.in_use(), 0);
+    }
+
+    #[test]
+    fn reservation_releases_on_drop() {
+        let b = MemoryBudget::unlimited();
+        b.set_limit_mb(Some(1));
+        {
+            let mut r = Reservation::new(&b);
+            assert!(r.grow(1000) && r.grow(500));
+            assert!(!r.grow(b.limit().unwrap()));
+            assert_eq!((r.bytes(), b.in_use()), (1500, 1500));
+        }
+        assert_eq!(b.in_use(), 0);
+        b.set_limit_mb(Some(usize::MAX));
+        assert!(b.limit().unwrap() > 1024*1024);
+    }
+}
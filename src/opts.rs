@@ -2,14 +2,24 @@ extern crate getopts;
 extern crate time;
 
 use std::env;
+use std::fmt;
 use std::process;
+use std::str::FromStr;
 use std::vec::Vec;
 
+use super::solver;
+use super::archive;
+use super::problems;
+use super::render;
+use super::export;
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct DavarOptions {
     pub ncores: usize,
     pub submit: bool,
     pub files: Vec<String>,
+    /// Where to look for problems if no files are given.
+    pub problems: String,
     pub time_limit: f64,
     pub memory_limit: Option<usize>,
     pub phrases_of_power: Vec<String>,
@@ -17,16 +27,153 @@ pub struct DavarOptions {
     pub solver: String,
     pub animate: Option<u32>,
     pub starting_time: f64,
-    pub seed: Option<i32>,
+    pub seed: Option<Selection>,
+    pub problem: Option<Selection>,
     pub solution: Option<String>,
     pub tag: Option<String>,
     pub verbose: bool,
+    pub parameters: Vec<(String, String)>,
+    pub archive: String,
+    pub export_best: Option<String>,
+    pub verify: bool,
+    pub submit_to: Option<String>,
+    pub dry_run: bool,
+    pub credentials: Option<String>,
+    pub replay: Option<String>,
+    pub report: Option<String>,
+    pub results: Option<String>,
+    /// "json" for one array once we are done, or "jsonl" for a line
+    /// per seed as soon as it is solved.
+    pub output: String,
+    /// How to draw boards for --animate and --verbose.
+    pub style: render::Style,
+    /// Write pictures of the solutions given to --replay here, or
+    /// else of the starting boards of the chosen problems.
+    pub export: Option<String>,
+    pub export_format: export::Format,
+    /// Where to write what the solvers decided, as JSON lines.
+    pub trace: Option<String>,
+    /// The seed for every solver's random numbers, rather than each
+    /// solver's own default.
+    pub rng_seed: Option<u64>,
+}
+
+/// Some numbers, given as a list of numbers and inclusive ranges
+/// like "3", "1,4,7" or "10-20,25".
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct Selection {
+    ranges: Vec<(i32, i32)>,
+}
+
+impl Selection {
+    pub fn contains(&self, x: i32) -> bool {
+        self.ranges.iter().any(|&(lo, hi)| lo <= x && x <= hi)
+    }
+    /// The numbers given on their own rather than as a range.  These
+    /// had better exist.
+    pub fn singles(&self) -> Vec<i32> {
+        self.ranges.iter().filter(|r| r.0 == r.1).map(|r| r.0).collect()
+    }
+}
+
+impl FromStr for Selection {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Selection, String> {
+        let num = |x: &str| x.trim().parse::<i32>().map_err(|_| format!("bad number \"{}\" in \"{}\"", x, s));
+        let mut ranges = Vec::new();
+        for part in s.split(',') {
+            // Look for the dash after the first character, so
+            // negative numbers still work.
+            let r = match part.trim().get(1..).and_then(|p| p.find('-')) {
+                Some(i) => (num(&part.trim()[..i+1])?, num(&part.trim()[i+2..])?),
+                None => (num(part)?, num(part)?),
+            };
+            if r.0 > r.1 {
+                return Err(format!("empty range \"{}\"", part));
+            }
+            ranges.push(r);
+        }
+        Ok(Selection { ranges })
+    }
+}
+
+impl fmt::Display for Selection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let parts: Vec<String> = self.ranges.iter().map(|&(lo, hi)| {
+            if lo == hi { format!("{}", lo) } else { format!("{}-{}", lo, hi) }
+        }).collect();
+        write!(f, "{}", parts.join(","))
+    }
 }
 
+#[test]
+fn parse_selection() {
+    let s: Selection = "3, 10-20,-2".parse().unwrap();
+    assert!(s.contains(3) && s.contains(10) && s.contains(15) && s.contains(20) && s.contains(-2));
+    assert!(!s.contains(4) && !s.contains(21) && !s.contains(-1));
+    assert_eq!(s.singles(), vec![3, -2]);
+    assert_eq!(format!("{}", s), "3,10-20,-2");
+    assert!("5-1".parse::<Selection>().is_err());
+    assert!("1,x".parse::<Selection>().is_err());
+    assert!("".parse::<Selection>().is_err());
+}
+
+impl Default for DavarOptions {
+    /// What you get without any flags.  The clock starts now.
+    fn default() -> DavarOptions {
+        DavarOptions {
+            ncores: 1,
+            submit: false,
+            files: Vec::new(),
+            problems: problems::DEFAULT_DIR.into(),
+            time_limit: 60.0*60.0*24.0, // one day time limit!
+            memory_limit: None,
+            phrases_of_power: Vec::new(),
+            save_solutions: false,
+            solver: "bottomupdfs".into(),
+            animate: None,
+            starting_time: time::precise_time_s(),
+            seed: None,
+            problem: None,
+            solution: None,
+            tag: None,
+            verbose: false,
+            parameters: Vec::new(),
+            archive: archive::DEFAULT_DIR.into(),
+            export_best: None,
+            verify: false,
+            submit_to: None,
+            dry_run: false,
+            credentials: None,
+            replay: None,
+            report: None,
+            results: None,
+            output: "json".into(),
+            style: render::Style::default(),
+            export: None,
+            export_format: export::Format::Gif,
+            trace: None,
+            rng_seed: None,
+        }
+    }
+}
 
 impl DavarOptions {
     pub fn time_left(&self) -> f64 {
-        self.time_limit as f64 - time::precise_time_s() + self.starting_time
+        self.time_limit - time::precise_time_s() + self.starting_time
+    }
+    /// The value of a solver parameter given with `--param`, or
+    /// `default` if it was not given.
+    pub fn parameter<T: FromStr>(&self, name: &str, default: T) -> T {
+        for (n, v) in self.parameters.iter().rev() {
+            if n == name {
+                match v.parse() {
+                    Ok(x) => return x,
+                    Err(_) => panic!("Bad value \"{}\" for parameter {}", v, name),
+                }
+            }
+        }
+        default
     }
 }
 
@@ -36,44 +183,71 @@ pub fn opts() -> DavarOptions {
 
     let mut opts = getopts::Options::new();
     opts.optflag("", "submit", "submit to server");
-    opts.optflag("", "save", "save solutions as files");
-    opts.optflag("", "verbose", "send verbosity to stdout");
+    opts.optopt("", "submit-to", "where to submit: a URL, file:PATH, or mock for a local test server", "TARGET");
+    opts.optopt("", "credentials", "file holding the API token (default ~/.davar-token)", "FILENAME");
+    opts.optflag("", "dry-run", "with --submit, show what would be posted instead of posting");
+    opts.optflag("", "save", "save solutions as files and in the archive");
+    opts.optopt("", "archive", "solution archive directory (default \"archive\")", "DIR");
+    opts.optopt("", "replay", "score (or with --animate, show) saved solutions and exit", "FILENAME");
+    opts.optopt("", "export", "write pictures of the --replay solutions, or else of the starting boards of the chosen problems, into this directory", "DIR");
+    opts.optopt("", "export-format", "svg for the final board, png for a picture every move, gif (the default), or html for a page to step through", "FORMAT");
+    opts.optopt("", "report", "print a score report of the archive as a table or json, and exit", "FORMAT");
+    opts.optopt("", "results", "with --report, report on this metadata.json from the server instead", "FILENAME");
+    opts.optflag("", "verify", "replay every archived solution, fix its scores and exit");
+    opts.optopt("", "export-best", "write the best archived solutions as one submission and exit", "FILENAME");
+    opts.optopt("", "output", "print solutions as one json array at the end (the default), or as jsonl, a line per seed as it is solved", "FORMAT");
+    opts.optflag("", "verbose", "print progress and diagnostics on stderr");
+    opts.optopt("", "trace", "write each decision the solver makes to this file, as json lines", "FILENAME");
     opts.optopt("", "solver", "name of solver algorithm", "ALGORITHM");
     opts.optopt("c", "", "number of cores", "NCORE");
     opts.optflag("h", "help", "print this help menu");
     opts.optmulti("f", "", "input filename", "FILENAME");
+    opts.optopt("", "problems", "without -f, solve every problem in this directory (default \"problems\")", "DIR");
     opts.optopt("t", "", "time limit", "SECONDS");
     opts.optopt("m", "", "memory limit", "MEGABYTES");
     opts.optmulti("p", "", "phrase of power", "PHRASE");
     opts.optopt("", "animate", "MILISECONDS", "display animation of solution");
-    opts.optopt("", "seed", "INT", "specify if you only want to run for a single seed");
-    opts.optopt("", "solution", "STRING", "Only used with \"supplied\" solver. Instead of running an algorithm, will just solve with this solution.");
+    opts.optopt("", "style", "how to draw boards: ascii (the default), ansi, hex or hex+ansi", "STYLE");
+    opts.optopt("", "seed", "only solve these seeds, e.g. 0 or 1,3,10-20", "SEEDS");
+    opts.optopt("", "problem", "only solve the problems with these ids, e.g. 3 or 0-5,7", "IDS");
+    opts.optopt("", "solution", "STRING", "With the \"supplied\" solver, just score this solution instead of running an algorithm.  The \"mc\" solver starts from it.");
     opts.optopt("", "tag", "STRING", "Override default tag with this.");
+    opts.optmulti("", "param", "set a parameter of the solver", "NAME=VALUE");
+    opts.optopt("", "rng-seed", "seed for the random numbers of solvers that use them, so a run can be repeated", "SEED");
     let matches = match opts.parse(&args[1..]) {
         Ok(m) => { m }
-        Err(f) => { panic!(f.to_string()) }
+        Err(f) => { panic!("{}", f) }
     };
     if matches.opt_present("h") {
         let brief = format!("Usage: {} [options]", program);
         print!("{}", opts.usage(&brief));
+        print!("\n{}", solver::describe_solvers());
         process::exit(0);
     }
     let mut davar_options = DavarOptions {
-        ncores: 1,
         submit: matches.opt_present("submit"),
         save_solutions: matches.opt_present("save"),
         verbose: matches.opt_present("verbose"),
         files: matches.opt_strs("f"),
-        time_limit: 60.0*60.0*24.0, // one day time limit!
-        memory_limit: None,
         phrases_of_power: matches.opt_strs("p"),
-        solver: "alldone".into(),
-        animate: None,
-        starting_time: time::precise_time_s(),
-        seed: None,
-        solution: None,
-        tag: None,
+        export_best: matches.opt_str("export-best"),
+        verify: matches.opt_present("verify"),
+        submit_to: matches.opt_str("submit-to"),
+        dry_run: matches.opt_present("dry-run"),
+        credentials: matches.opt_str("credentials"),
+        replay: matches.opt_str("replay"),
+        report: matches.opt_str("report"),
+        results: matches.opt_str("results"),
+        export: matches.opt_str("export"),
+        trace: matches.opt_str("trace"),
+        ..DavarOptions::default()
     };
+    if let Some(dir) = matches.opt_str("problems") {
+        davar_options.problems = dir;
+    }
+    if let Some(output) = matches.opt_str("output") {
+        davar_options.output = output;
+    }
     if let Some(nc) = matches.opt_str("c") {
         davar_options.ncores = nc.parse().unwrap();
     }
@@ -86,15 +260,51 @@ pub fn opts() -> DavarOptions {
     if let Some(alg) = matches.opt_str("solver") {
         davar_options.solver = alg;
     }
+    if let Some(s) = matches.opt_str("rng-seed") {
+        davar_options.rng_seed = match s.parse() {
+            Ok(seed) => Some(seed),
+            Err(_) => panic!("--rng-seed must be a number, not \"{}\"", s),
+        };
+    }
     if let Some(a) = matches.opt_str("animate") {
         davar_options.animate = Some(a.parse().unwrap());
     }
-    if let Some(s) = matches.opt_str("seed") {
-        davar_options.seed = Some(s.parse().unwrap());
+    if let Some(s) = matches.opt_str("style") {
+        davar_options.style = match s.parse() {
+            Ok(style) => style,
+            Err(e) => panic!("--style: {}", e),
+        };
+    }
+    if let Some(f) = matches.opt_str("export-format") {
+        davar_options.export_format = match f.parse() {
+            Ok(format) => format,
+            Err(e) => panic!("--export-format: {}", e),
+        };
     }
+    let selection = |name: &str| matches.opt_str(name).map(|s| match s.parse::<Selection>() {
+        Ok(x) => x,
+        Err(e) => panic!("--{}: {}", name, e),
+    });
+    davar_options.seed = selection("seed");
+    davar_options.problem = selection("problem");
 
     davar_options.solution = matches.opt_str("solution");
+    if let Some((i, c)) = davar_options.solution.as_ref().and_then(|s| super::bad_command(s)) {
+        panic!("--solution has {:?} at {}, which is not a command", c, i);
+    }
     davar_options.tag = matches.opt_str("tag");
+    if let Some(dir) = matches.opt_str("archive") {
+        davar_options.archive = dir;
+    }
+    if davar_options.output != "json" && davar_options.output != "jsonl" {
+        panic!("--output must be json or jsonl, not \"{}\"", davar_options.output);
+    }
+    for p in matches.opt_strs("param") {
+        match p.find('=') {
+            Some(i) => davar_options.parameters.push((p[..i].into(), p[i+1..].into())),
+            None => panic!("--param needs NAME=VALUE, not \"{}\"", p),
+        }
+    }
 
     davar_options
 }
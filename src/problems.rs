@@ -0,0 +1,211 @@
+//! Every problem we know about, by its id.
+//!
+//! Problems are read from a directory (every `.json` file in it) or
+//! from a list of files, and looked up by the `id` in their JSON, so
+//! nothing needs to know what the files are called.
+
+use super::*;
+use super::opts::Selection;
+
+use serde_json;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_DIR: &str = "problems";
+
+#[derive(Debug)]
+pub enum ProblemsError {
+    Io(PathBuf, io::Error),
+    Json(PathBuf, serde_json::Error),
+    /// Two files with the same problem id.
+    Duplicate(i32, PathBuf, PathBuf),
+    NoSuchProblem(i32),
+    /// A problem and a seed it does not have.
+    NoSuchSeed(i32, i32),
+    NothingSelected,
+}
+
+impl fmt::Display for ProblemsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ProblemsError::Io(ref p, ref e) => write!(f, "couldn't read {}: {}", p.display(), e),
+            ProblemsError::Json(ref p, ref e) => write!(f, "{} is not a problem: {}", p.display(), e),
+            ProblemsError::Duplicate(id, ref a, ref b) =>
+                write!(f, "{} and {} are both problem {}", a.display(), b.display(), id),
+            ProblemsError::NoSuchProblem(id) => write!(f, "there is no problem {}", id),
+            ProblemsError::NoSuchSeed(id, seed) => write!(f, "problem {} has no seed {}", id, seed),
+            ProblemsError::NothingSelected => write!(f, "no problem has the seeds asked for"),
+        }
+    }
+}
+
+impl Error for ProblemsError {}
+
+#[derive(Debug, Clone, Default)]
+pub struct Problems {
+    problems: BTreeMap<i32, (PathBuf, Input)>,
+}
+
+impl Problems {
+    pub fn new() -> Problems {
+        Problems { problems: BTreeMap::new() }
+    }
+    /// Every `.json` file in `dir`.
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Result<Problems, ProblemsError> {
+        let dir = dir.as_ref();
+        let mut paths = Vec::new();
+        let entries = fs::read_dir(dir).map_err(|e| ProblemsError::Io(dir.to_path_buf(), e))?;
+        for e in entries {
+            let path = e.map_err(|e| ProblemsError::Io(dir.to_path_buf(), e))?.path();
+            if path.is_file() && path.extension() == Some("json".as_ref()) {
+                paths.push(path);
+            }
+        }
+        // So that which of two duplicates we complain about does not
+        // depend on the file system.
+        paths.sort();
+        Problems::from_files(&paths)
+    }
+    pub fn from_files<P: AsRef<Path>>(files: &[P]) -> Result<Problems, ProblemsError> {
+        let mut ps = Problems::new();
+        for f in files {
+            ps.add_file(f)?;
+        }
+        Ok(ps)
+    }
+    pub fn add_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), ProblemsError> {
+        let path = path.as_ref();
+        let mut contents = String::new();
+        File::open(path).and_then(|mut f| f.read_to_string(&mut contents))
+            .map_err(|e| ProblemsError::Io(path.to_path_buf(), e))?;
+        let input: Input = serde_json::from_str(&contents)
+            .map_err(|e| ProblemsError::Json(path.to_path_buf(), e))?;
+        self.add(path, input)
+    }
+    /// Add `input`, which came from `path`.
+    pub fn add<P: AsRef<Path>>(&mut self, path: P, input: Input) -> Result<(), ProblemsError> {
+        let path = path.as_ref().to_path_buf();
+        if let Some((old, _)) = self.problems.get(&input.id) {
+            return Err(ProblemsError::Duplicate(input.id, old.clone(), path));
+        }
+        self.problems.insert(input.id, (path, input));
+        Ok(())
+    }
+
+    pub fn get(&self, id: i32) -> Option<&Input> {
+        self.problems.get(&id).map(|p| &p.1)
+    }
+    /// The file problem `id` was read from.
+    pub fn path(&self, id: i32) -> Option<&Path> {
+        self.problems.get(&id).map(|p| p.0.as_path())
+    }
+    /// All the problems, ordered by id.
+    pub fn inputs(&self) -> Vec<&Input> {
+        self.problems.values().map(|p| &p.1).collect()
+    }
+    pub fn ids(&self) -> Vec<i32> {
+        self.problems.keys().cloned().collect()
+    }
+    pub fn len(&self) -> usize {
+        self.problems.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.problems.is_empty()
+    }
+    /// The starting state for `seed` of problem `id`.
+    pub fn initial_state(&self, id: i32, seed: i32) -> Option<State> {
+        self.get(id).and_then(|input| {
+            input_to_states(input).into_iter().find(|s| s.seed == seed)
+        })
+    }
+    /// The starting states of the chosen problems and seeds (or of
+    /// all of them if `None`), with their problems.  Every problem or
+    /// seed asked for by itself rather than in a range must exist.
+    /// Some problems list a seed twice, but we only solve it once.
+    pub fn select(&self, problems: Option<&Selection>, seeds: Option<&Selection>)
+                  -> Result<Vec<(&Input, State)>, ProblemsError> {
+        if let Some(ps) = problems {
+            if let Some(&id) = ps.singles().iter().find(|&&id| self.get(id).is_none()) {
+                return Err(ProblemsError::NoSuchProblem(id));
+            }
+        }
+        let mut out = Vec::new();
+        for input in self.inputs() {
+            if let Some(ps) = problems {
+                if !ps.contains(input.id) {
+                    continue;
+                }
+            }
+            if let Some(ss) = seeds {
+                if let Some(&seed) = ss.singles().iter().find(|s| !input.source_seeds.contains(s)) {
+                    return Err(ProblemsError::NoSuchSeed(input.id, seed));
+                }
+            }
+            let mut seen = Vec::new();
+            for state in input_to_states(input) {
+                let wanted = match seeds {
+                    Some(ss) => ss.contains(state.seed),
+                    None => true,
+                };
+                if wanted && !seen.contains(&state.seed) {
+                    seen.push(state.seed);
+                    out.push((input, state));
+                }
+            }
+        }
+        if out.is_empty() {
+            return Err(ProblemsError::NothingSelected);
+        }
+        Ok(out)
+    }
+}
+
+#[test]
+fn problems_by_id() {
+    let ps = Problems::from_dir(DEFAULT_DIR).unwrap();
+    assert_eq!(ps.len(), 25);
+    assert_eq!(ps.ids(), (0..25).collect::<Vec<i32>>());
+    for input in ps.inputs() {
+        let path = ps.path(input.id).unwrap();
+        assert_eq!(Input::from_json(path), *input);
+    }
+    let s = ps.initial_state(0, 0).unwrap();
+    assert_eq!(s, input_to_states(&Input::from_json("problems/problem_0.json"))[0]);
+    assert_eq!(ps.initial_state(0, 1), None);
+    assert_eq!(ps.initial_state(100, 0), None);
+
+    let sel = |s: &str| s.parse::<Selection>().unwrap();
+    // Problem 5 lists seed 22837 twice.
+    assert_eq!(ps.select(None, None).unwrap().len(),
+               ps.inputs().iter().map(|p| p.source_seeds.len()).sum::<usize>() - 1);
+    let chosen = ps.select(Some(&sel("1,3-4")), Some(&sel("0"))).unwrap();
+    assert_eq!(chosen.iter().map(|c| (c.0.id, c.1.seed)).collect::<Vec<_>>(),
+               vec![(1, 0), (3, 0), (4, 0)]);
+    let chosen = ps.select(Some(&sel("5")), Some(&sel("22837"))).unwrap();
+    assert_eq!(chosen.len(), 1);
+    assert_eq!(chosen[0].1.seed, 22837);
+    assert_eq!(ps.select(Some(&sel("2-3")), Some(&sel("1-14000"))).unwrap().len(), 5);
+    match ps.select(Some(&sel("0-100")), Some(&sel("1"))) {
+        Err(ProblemsError::NoSuchSeed(0, 1)) => (),
+        x => panic!("expected NoSuchSeed, got {:?}", x.map(|v| v.len())),
+    }
+    match ps.select(Some(&sel("7,99")), None) {
+        Err(ProblemsError::NoSuchProblem(99)) => (),
+        x => panic!("expected NoSuchProblem, got {:?}", x.map(|v| v.len())),
+    }
+    match ps.select(None, Some(&sel("-5--3"))) {
+        Err(ProblemsError::NothingSelected) => (),
+        x => panic!("expected NothingSelected, got {:?}", x.map(|v| v.len())),
+    }
+
+    let mut ps = Problems::from_files(&["problems/problem_3.json"]).unwrap();
+    assert_eq!(ps.ids(), vec![3]);
+    match ps.add_file("problems/problem_3.json") {
+        Err(ProblemsError::Duplicate(3, _, _)) => (),
+        x => panic!("expected Duplicate, got {:?}", x),
+    }
+}
@@ -0,0 +1,277 @@
+//! Drawing a board: as plain text, in color for a terminal, or with
+//! Unicode hexagons, with annotations on top (places a piece could go,
+//! the path it takes there, the rows it would clear).
+//!
+//! Every style uses the same layout: two columns a cell, with odd rows
+//! shifted half a cell to the right, between `|` borders.
+
+use super::*;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// How to draw, e.g. "ascii" (the default), "ansi", "hex" or
+/// "hex+ansi".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Style {
+    /// Unicode hexagons instead of ASCII letters.
+    pub hex: bool,
+    /// ANSI color escapes.
+    pub color: bool,
+}
+
+impl FromStr for Style {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Style, String> {
+        let mut style = Style::default();
+        for part in s.split('+') {
+            match part.trim() {
+                "ascii" => (),
+                "ansi" | "color" => style.color = true,
+                "hex" => style.hex = true,
+                _ => return Err(format!("unknown style \"{}\" (try ascii, ansi, hex or hex+ansi)", part)),
+            }
+        }
+        Ok(style)
+    }
+}
+
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.hex, self.color) {
+            (false, false) => write!(f, "ascii"),
+            (false, true) => write!(f, "ansi"),
+            (true, false) => write!(f, "hex"),
+            (true, true) => write!(f, "hex+ansi"),
+        }
+    }
+}
+
+/// An annotation on a cell.  Where a cell has more than one, the
+/// later one here wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Mark {
+    /// Somewhere the current piece could lock.
+    Candidate,
+    /// Somewhere the current piece passes through.
+    Path,
+    /// A cell in a row that would clear.
+    Clear,
+    /// The place chosen out of the candidates.
+    Selected,
+}
+
+/// What a cell looks like.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Look {
+    Empty,
+    Filled,
+    /// Part of the current piece.
+    Piece,
+    /// The pivot of the current piece, which is part of it.
+    Pivot,
+    /// The pivot of the current piece, outside of it.
+    BarePivot,
+    Marked(Mark),
+}
+
+/// Every look there is.
+pub const LOOKS: [Look; 9] = [Look::Empty, Look::Filled, Look::Piece, Look::Pivot, Look::BarePivot,
+                              Look::Marked(Mark::Candidate), Look::Marked(Mark::Path),
+                              Look::Marked(Mark::Clear), Look::Marked(Mark::Selected)];
+
+impl Look {
+    /// The character for this look, in ASCII or as a hexagon.
+    pub fn glyph(self, hex: bool) -> char {
+        match (self, hex) {
+            (Look::Empty, false) => '.',
+            (Look::Filled, false) => 'X',
+            (Look::Piece, false) => 'O',
+            (Look::Pivot, false) => '#',
+            (Look::BarePivot, false) => '*',
+            (Look::Marked(Mark::Candidate), false) => '+',
+            (Look::Marked(Mark::Path), false) => ':',
+            (Look::Marked(Mark::Clear), false) => '=',
+            (Look::Marked(Mark::Selected), false) => '@',
+            (Look::Empty, true) => '⬡',
+            (Look::Filled, true) => '⬢',
+            (Look::Piece, true) => '●',
+            (Look::Pivot, true) => '◉',
+            (Look::BarePivot, true) => '◌',
+            (Look::Marked(Mark::Candidate), true) => '◇',
+            (Look::Marked(Mark::Path), true) => '·',
+            (Look::Marked(Mark::Clear), true) => '▣',
+            (Look::Marked(Mark::Selected), true) => '◆',
+        }
+    }
+    /// The ANSI graphic rendition for this look.
+    fn sgr(self) -> &'static str {
+        match self {
+            Look::Empty => "2",
+            Look::Filled => "1;34",
+            Look::Piece => "1;33",
+            Look::Pivot => "1;31",
+            Look::BarePivot => "31",
+            Look::Marked(Mark::Candidate) => "32",
+            Look::Marked(Mark::Path) => "36",
+            Look::Marked(Mark::Clear) => "1;35",
+            Look::Marked(Mark::Selected) => "1;7;32",
+        }
+    }
+}
+
+/// A board to draw, with its annotations.
+pub struct Render<'a> {
+    state: &'a State,
+    style: Style,
+    marks: HashMap<Cell, Mark>,
+}
+
+impl<'a> Render<'a> {
+    pub fn new(state: &'a State, style: Style) -> Render<'a> {
+        Render { state, style, marks: HashMap::new() }
+    }
+    pub fn style(&self) -> Style {
+        self.style
+    }
+    pub fn state(&self) -> &'a State {
+        self.state
+    }
+
+    /// Mark `cells`, unless they already have a mark that wins.
+    pub fn mark(&mut self, cells: &[Cell], mark: Mark) -> &mut Render<'a> {
+        for &c in cells {
+            let m = self.marks.entry(c).or_insert(mark);
+            if mark > *m {
+                *m = mark;
+            }
+        }
+        self
+    }
+    /// Places the current piece could lock.
+    pub fn candidates(&mut self, units: &[Unit]) -> &mut Render<'a> {
+        for u in units {
+            self.mark(&u.members, Mark::Candidate);
+        }
+        self
+    }
+    /// Everywhere the current piece of `from` goes while playing
+    /// `letters`, up to where it locks.
+    pub fn path(&mut self, from: &State, letters: &str) -> &mut Render<'a> {
+        let mut s = from.clone();
+        let pieces = s.unit_sequence.len();
+        for cmd in string_to_commands(letters) {
+            if s.game_over || s.unit_sequence.len() != pieces {
+                break;
+            }
+            let cells = s.unit_sequence[0].members.clone();
+            self.mark(&cells, Mark::Path);
+            s = s.apply(cmd);
+        }
+        self
+    }
+    /// The rows that would clear if `unit` locked where it is.
+    pub fn clears(&mut self, unit: &Unit) -> &mut Render<'a> {
+        let s = self.state;
+        for y in 0 .. s.height {
+            let row: Vec<Cell> = (0 .. s.width).map(|x| Cell::new(x, y)).collect();
+            if row.iter().any(|c| unit.members.contains(c)) &&
+                row.iter().all(|&c| s.is_filled(c) || unit.members.contains(&c)) {
+                self.mark(&row, Mark::Clear);
+            }
+        }
+        self
+    }
+
+    /// What cell `c` looks like.  The current piece shows over any
+    /// mark, and filled cells only show that they would clear.
+    pub fn look(&self, c: Cell) -> Look {
+        let piece = self.state.unit_sequence.first();
+        let mark = self.marks.get(&c).cloned();
+        if self.state.is_filled(c) {
+            return match mark {
+                Some(Mark::Clear) => Look::Marked(Mark::Clear),
+                _ => Look::Filled,
+            };
+        }
+        match piece {
+            Some(u) if u.pivot == c && u.members.contains(&c) => Look::Pivot,
+            Some(u) if u.pivot == c => Look::BarePivot,
+            Some(u) if u.members.contains(&c) => Look::Piece,
+            _ => mark.map(Look::Marked).unwrap_or(Look::Empty),
+        }
+    }
+
+    /// The board as pieces of text, each a cell (with how it looks)
+    /// or the spaces and borders between them.  Each row ends with a
+    /// newline.
+    pub fn pieces(&self) -> Vec<(String, Option<Look>)> {
+        let mut out = Vec::new();
+        for y in 0 .. self.state.height {
+            out.push((if y%2 == 1 { "| " } else { "|" }.to_string(), None));
+            for x in 0 .. self.state.width {
+                let look = self.look(Cell::new(x, y));
+                out.push((" ".to_string(), None));
+                out.push((look.glyph(self.style.hex).to_string(), Some(look)));
+            }
+            out.push((if y%2 == 0 { " |\n" } else { "|\n" }.to_string(), None));
+        }
+        out
+    }
+}
+
+impl<'a> fmt::Display for Render<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (text, look) in self.pieces() {
+            match look {
+                Some(l) if self.style.color => write!(f, "\x1b[{}m{}\x1b[0m", l.sgr(), text)?,
+                _ => write!(f, "{}", text)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn render_with_marks() {
+    let s = input_to_states(&Input::from_json("problems/problem_1.json"))[0].clone();
+    assert_eq!(format!("{}", Render::new(&s, Style::default())), s.visualize());
+
+    let lines = |r: &Render| format!("{}", r).lines().map(|l| l.to_string()).collect::<Vec<_>>();
+    let plain = lines(&Render::new(&s, Style::default()));
+    let hex = lines(&Render::new(&s, "hex".parse().unwrap()));
+    assert_eq!(plain.len(), s.height as usize);
+    for (p, h) in plain.iter().zip(hex.iter()) {
+        assert_eq!(p.chars().count(), h.chars().count());
+    }
+    let colored = format!("{}", Render::new(&s, "ansi".parse().unwrap()));
+    assert!(colored.contains("\x1b[1;34mX\x1b[0m"));
+
+    // Walk the piece down and lock it at the bottom left.
+    let path = "ei!lllaaaall";
+    let mut r = Render::new(&s, Style::default());
+    r.path(&s, path);
+    let at = s.apply_sequence(&string_to_commands("ei!lllaaaal"));
+    let marked: Vec<Cell> = r.marks.keys().cloned().collect();
+    assert!(at.unit_sequence[0].members.iter().all(|c| marked.contains(c)));
+    assert!(s.unit_sequence[0].members.iter().all(|c| r.look(*c) == Look::Piece ||
+                                                   r.look(*c) == Look::Pivot));
+    r.mark(&[Cell::new(0, 0)], Mark::Selected).mark(&[Cell::new(0, 0)], Mark::Candidate);
+    assert_eq!(r.marks[&Cell::new(0, 0)], Mark::Selected);
+
+    // A row missing one cell clears when a piece fills it.
+    let mut full = s.clone();
+    full.unit_sequence.clear();
+    for x in 1 .. full.width {
+        full.filled_array[(full.width*(full.height - 1) + x) as usize] = true;
+    }
+    let unit = Unit { members: vec![Cell::new(0, full.height - 1)], pivot: Cell::new(0, full.height - 1) };
+    let mut r = Render::new(&full, Style::default());
+    r.clears(&unit);
+    let last = lines(&r).pop().unwrap();
+    assert_eq!(last.matches('=').count(), full.width as usize);
+    let mut r = Render::new(&full, Style::default());
+    r.clears(&Unit { members: vec![Cell::new(0, 0)], pivot: Cell::new(0, 0) });
+    assert!(!format!("{}", r).contains('='));
+}
@@ -0,0 +1,212 @@
+//! Score reports in the style of the contest rankings.
+//!
+//! The contest ranked teams on each problem by their score averaged
+//! over all of its seeds (a seed we have no solution for counts as
+//! zero), so that is what we report, along with every seed's score
+//! and how often each phrase of power was used.
+
+use super::*;
+use super::archive::Entry;
+
+use serde_json;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+#[derive(Debug, Eq, PartialEq, Clone, Hash, Serialize)]
+pub struct PhraseCount {
+    pub phrase: String,
+    /// How many times the phrase was used.
+    pub uses: usize,
+    /// How many solutions used it at least once.
+    pub solutions: usize,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Hash, Serialize)]
+pub struct SeedResult {
+    pub seed: i32,
+    pub move_score: Score,
+    pub power_score: Score,
+    pub total_score: Score,
+    pub tag: Option<String>,
+    pub phrases: Vec<PhraseCount>,
+}
+
+impl SeedResult {
+    pub fn new(seed: i32, move_score: Score, power_score: Score, tag: Option<String>,
+               solution: &str, phrases: &[String]) -> SeedResult {
+        SeedResult {
+            seed,
+            move_score,
+            power_score,
+            total_score: move_score + power_score,
+            tag,
+            phrases: phrases.iter().map(|p| {
+                let uses = simulate::count_substrings(solution, p);
+                PhraseCount {
+                    phrase: p.clone(),
+                    uses,
+                    solutions: if uses > 0 { 1 } else { 0 },
+                }
+            }).collect(),
+        }
+    }
+    pub fn from_entry(e: &Entry, phrases: &[String]) -> SeedResult {
+        SeedResult::new(e.seed, e.move_score, e.power_score, e.tag.clone(), &e.solution, phrases)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct ProblemReport {
+    pub problem_id: i32,
+    pub seeds: Vec<SeedResult>,
+    pub missing_seeds: Vec<i32>,
+    pub average_move: f64,
+    pub average_power: f64,
+    pub average: f64,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Report {
+    pub problems: Vec<ProblemReport>,
+    pub phrases: Vec<PhraseCount>,
+    pub seeds_solved: usize,
+    pub seeds_total: usize,
+    /// The sum of the per-problem averages.
+    pub total: f64,
+}
+
+impl Report {
+    /// Build a report from (problem id, result) pairs.  `seeds_of`
+    /// gives all the seeds of a problem, so we can count the ones we
+    /// are missing.
+    pub fn new<F>(results: Vec<(i32, SeedResult)>, phrases: &[String], mut seeds_of: F) -> Report
+        where F: FnMut(i32) -> Vec<i32>
+    {
+        let mut by_problem: BTreeMap<i32, Vec<SeedResult>> = BTreeMap::new();
+        for (p, r) in results {
+            by_problem.entry(p).or_default().push(r);
+        }
+        let mut phrase_counts: Vec<PhraseCount> = phrases.iter().map(|p| {
+            PhraseCount { phrase: p.clone(), uses: 0, solutions: 0 }
+        }).collect();
+        let mut problems = Vec::new();
+        let (mut seeds_solved, mut seeds_total, mut total) = (0, 0, 0.0);
+        for (problem_id, mut seeds) in by_problem {
+            seeds.sort_by_key(|r| r.seed);
+            let all_seeds = seeds_of(problem_id);
+            let missing: Vec<i32> = all_seeds.iter().cloned()
+                .filter(|s| !seeds.iter().any(|r| r.seed == *s)).collect();
+            let n = ::std::cmp::max(1, seeds.len() + missing.len()) as f64;
+            let sum = |f: &dyn Fn(&SeedResult) -> Score| seeds.iter().fold(0, |acc, r| acc + f(r)) as f64;
+            let average_move = sum(&|r| r.move_score) / n;
+            let average_power = sum(&|r| r.power_score) / n;
+            for r in seeds.iter() {
+                for (c, rc) in phrase_counts.iter_mut().zip(r.phrases.iter()) {
+                    c.uses += rc.uses;
+                    c.solutions += rc.solutions;
+                }
+            }
+            seeds_solved += seeds.len();
+            seeds_total += seeds.len() + missing.len();
+            total += average_move + average_power;
+            problems.push(ProblemReport {
+                problem_id,
+                seeds,
+                missing_seeds: missing,
+                average_move,
+                average_power,
+                average: average_move + average_power,
+            });
+        }
+        Report {
+            problems,
+            phrases: phrase_counts,
+            seeds_solved,
+            seeds_total,
+            total,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn to_table(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "{:>7} {:>7} {:>10} {:>10} {:>10}",
+                 "problem", "seeds", "average", "move", "power").unwrap();
+        for p in self.problems.iter() {
+            writeln!(out, "{:>7} {:>7} {:>10.1} {:>10.1} {:>10.1}", p.problem_id,
+                     format!("{}/{}", p.seeds.len(), p.seeds.len() + p.missing_seeds.len()),
+                     p.average, p.average_move, p.average_power).unwrap();
+            for r in p.seeds.iter() {
+                writeln!(out, "{:>15} {:>6}: {} = {} + {}  {}", "seed", r.seed,
+                         r.total_score, r.move_score, r.power_score,
+                         r.tag.clone().unwrap_or(String::new())).unwrap();
+            }
+            for s in p.missing_seeds.iter() {
+                writeln!(out, "{:>15} {:>6}: missing", "seed", s).unwrap();
+            }
+        }
+        if !self.phrases.is_empty() {
+            writeln!(out).unwrap();
+            writeln!(out, "{:<40} {:>6} {:>9}", "phrase", "uses", "solutions").unwrap();
+            for c in self.phrases.iter() {
+                writeln!(out, "{:<40} {:>6} {:>9}", c.phrase, c.uses, c.solutions).unwrap();
+            }
+        }
+        writeln!(out).unwrap();
+        writeln!(out, "{} problems, {}/{} seeds solved, total of averages {:.1}",
+                 self.problems.len(), self.seeds_solved, self.seeds_total, self.total).unwrap();
+        out
+    }
+}
+
+/// Read the results the contest server gives for our team (its
+/// `metadata.json`), keeping the best for each problem and seed.
+pub fn server_results<P: AsRef<Path>>(fname: P, phrases: &[String]) -> Vec<(i32, SeedResult)> {
+    let mut contents = String::new();
+    File::open(fname).and_then(|mut f| f.read_to_string(&mut contents))
+        .expect("Couldn't read the server results.");
+    let results = match serde_json::from_str(&contents) {
+        Ok(serde_json::Value::Array(a)) => a,
+        _ => panic!("Expected a JSON array of results from the server."),
+    };
+    let mut best: BTreeMap<(i32, i32), SeedResult> = BTreeMap::new();
+    for r in results.iter() {
+        let int = |k: &str| r.get(k).and_then(|v| v.as_i64()).unwrap_or(0) as i32;
+        let string = |k: &str| r.get(k).and_then(|v| v.as_str()).map(|s| s.to_string());
+        let result = SeedResult::new(int("seed"), int("score"), int("powerScore"), string("tag"),
+                                     &string("solution").unwrap_or(String::new()), phrases);
+        let key = (int("problemId"), result.seed);
+        let better = match best.get(&key) {
+            Some(old) => result.total_score > old.total_score,
+            None => true,
+        };
+        if better {
+            best.insert(key, result);
+        }
+    }
+    best.into_iter().map(|((p, _), r)| (p, r)).collect()
+}
+
+#[test]
+fn averages_count_missing_seeds() {
+    let phrases = vec!["ei!".to_string()];
+    let results = vec![(1, SeedResult::new(0, 100, 312, None, "ei!lei!", &phrases)),
+                       (1, SeedResult::new(5, 50, 0, None, "lll", &phrases)),
+                       (2, SeedResult::new(7, 10, 0, None, "l", &phrases))];
+    let report = Report::new(results, &phrases, |p| if p == 1 { vec![0, 5, 9, 11] } else { vec![7] });
+    assert_eq!(report.problems.len(), 2);
+    assert_eq!(report.problems[0].missing_seeds, vec![9, 11]);
+    assert_eq!(report.problems[0].average, (100.0 + 312.0 + 50.0)/4.0);
+    assert_eq!(report.problems[1].average, 10.0);
+    assert_eq!(report.seeds_solved, 3);
+    assert_eq!(report.seeds_total, 5);
+    assert_eq!(report.total, (100.0 + 312.0 + 50.0)/4.0 + 10.0);
+    assert_eq!(report.phrases[0].solutions, 1);
+    println!("{}", report.to_table());
+}
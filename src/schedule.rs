@@ -0,0 +1,155 @@
+//! Sharing the time budget across every (problem, seed) pair.
+//!
+//! All the jobs go into one queue that every thread pulls from, so
+//! an idle thread just takes the next job instead of waiting on a
+//! slow one.  When a thread takes a job, it gets a share of the
+//! remaining core-seconds proportional to the job's weight (board
+//! area times number of pieces) over the weight of everything not
+//! yet started.  Jobs that used up their whole share are assumed to
+//! still be improving, and once the queue is empty any leftover time
+//! is spent re-running them.  Results go to the `anytime` store,
+//! which keeps whichever scores better.  With `--output jsonl` each
+//! result is also printed as soon as its job finishes.
+
+use super::*;
+use super::opts::DavarOptions;
+use super::solver::Solve;
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+
+/// Time we hold back at the end for printing and saving.
+pub const BUFFER_TIME: f64 = 0.5;
+
+/// A job counts as converged if it finished in less than this
+/// fraction of the time it was given.
+const CONVERGED_FRACTION: f64 = 0.9;
+
+/// Do not bother re-running jobs with less time than this.
+const MIN_RETRY_TIME: f64 = 0.5;
+
+pub struct Job {
+    pub state: State,
+    pub input: Input,
+    pub weight: f64,
+}
+
+impl Job {
+    pub fn new(state: State, input: Input) -> Job {
+        let weight = (input.width*input.height) as f64 * state.unit_sequence.len() as f64;
+        Job {
+            state,
+            input,
+            weight: if weight > 0.0 { weight } else { 1.0 },
+        }
+    }
+}
+
+struct Queue {
+    waiting: VecDeque<usize>,
+    waiting_weight: f64,
+    improving: Vec<usize>,
+}
+
+pub struct Scheduler {
+    jobs: Vec<Job>,
+    options: DavarOptions,
+    queue: Mutex<Queue>,
+    /// The best score we have printed for each (problem, seed).
+    streamed: Mutex<BTreeMap<(i32, i32), Score>>,
+}
+
+impl Scheduler {
+    pub fn new(jobs: Vec<Job>, options: &DavarOptions) -> Scheduler {
+        let total_weight = jobs.iter().fold(0.0, |acc, j| acc + j.weight);
+        let njobs = jobs.len();
+        Scheduler {
+            jobs,
+            options: options.clone(),
+            queue: Mutex::new(Queue {
+                waiting: (0 .. njobs).collect(),
+                waiting_weight: total_weight,
+                improving: Vec::new(),
+            }),
+            streamed: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    fn time_left(&self) -> f64 {
+        self.options.time_left() - BUFFER_TIME
+    }
+
+    /// Pick the next job and how many seconds it may take.
+    fn next_job(&self) -> Option<(usize, f64)> {
+        let ncores = ::std::cmp::max(1, self.options.ncores) as f64;
+        if anytime::stop_requested() {
+            return None;
+        }
+        let mut q = self.queue.lock().unwrap();
+        let left = self.time_left();
+        if let Some(i) = q.waiting.pop_front() {
+            let share = self.jobs[i].weight / q.waiting_weight;
+            q.waiting_weight -= self.jobs[i].weight;
+            let budget = left*ncores*share;
+            return Some((i, if budget < left { budget } else { left }));
+        }
+        // Nothing new to start, so hand leftover time to jobs that
+        // were still improving, splitting it between those not yet
+        // retried and the cores.
+        if !q.improving.is_empty() {
+            let nleft = q.improving.len() as f64;
+            let budget = left * if nleft < ncores { 1.0 } else { ncores/nleft };
+            if budget >= MIN_RETRY_TIME {
+                let i = q.improving.remove(0);
+                return Some((i, budget));
+            }
+        }
+        None
+    }
+
+    /// Work through jobs until none are left or time runs out.  Call
+    /// this from each solver thread.
+    pub fn run(&self, solver: &dyn Solve) {
+        let store = anytime::global();
+        while let Some((i, budget)) = self.next_job() {
+            let job = &self.jobs[i];
+            let mut opts = self.options.clone();
+            let started = time::precise_time_s();
+            opts.time_limit = started - opts.starting_time + budget;
+            let before = store.best_score(job.input.id, job.state.seed);
+            let (sol, sc) = solver.solve(&job.state, &job.input, &opts);
+            let elapsed = time::precise_time_s() - started;
+            if self.options.verbose {
+                eprintln!("finished {}[{}, {}] = {} in {:.1}/{:.1} seconds",
+                          solver.name(), sol.problem_id, sol.seed, sc, elapsed, budget);
+            }
+            if self.options.output == "jsonl" {
+                self.stream(&sol, sc);
+            }
+            store.publish(sol, sc);
+            let improved = match before {
+                Some(old) => sc > old,
+                None => true,
+            };
+            let mut q = self.queue.lock().unwrap();
+            if improved && elapsed > CONVERGED_FRACTION*budget {
+                q.improving.push(i);
+            }
+        }
+    }
+
+    /// Print `sol` as a line of JSON, unless we already printed one
+    /// for its seed that scores at least as well.
+    pub fn stream(&self, sol: &Solution, sc: Score) {
+        let mut streamed = self.streamed.lock().unwrap();
+        let key = (sol.problem_id, sol.seed);
+        let better = match streamed.get(&key) {
+            Some(&old) => sc > old,
+            None => true,
+        };
+        if better {
+            streamed.insert(key, sc);
+            in_out::print_progress(sol, sc, time::precise_time_s() - self.options.starting_time);
+        }
+    }
+}
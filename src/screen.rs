@@ -0,0 +1,81 @@
+//! The curses setup shared by our interactive programs.
+
+use ncurses::*;
+use render::{Look, Mark, Render};
+
+/// Curses is running while this lives.
+pub struct Screen;
+
+impl Screen {
+    pub fn start() -> Screen {
+        // For the hexagons of `render`.
+        setlocale(LcCategory::all, "").ok();
+        initscr();
+        if has_colors() {
+            start_color();
+            use_default_colors();
+            for (pair, &color) in COLORS.iter().enumerate() {
+                init_pair(pair as i16 + 1, color, -1);
+            }
+        }
+        noecho();
+        keypad(stdscr(), true);
+        curs_set(CURSOR_VISIBILITY::CURSOR_INVISIBLE);
+        Screen
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        endwin();
+    }
+}
+
+/// Print at the cursor.  We never have NULs to print.
+pub fn put(s: &str) {
+    addstr(s).unwrap();
+}
+
+/// Print `s` with attributes `attr`, e.g. `A_BOLD`.
+pub fn put_with(attr: attr_t, s: &str) {
+    attron(attr);
+    put(s);
+    attroff(attr);
+}
+
+/// The colors of the pairs we set up, from pair 1.
+const COLORS: [i16; 6] = [COLOR_BLUE, COLOR_YELLOW, COLOR_RED, COLOR_GREEN, COLOR_CYAN, COLOR_MAGENTA];
+
+/// Print a board at the cursor, highlighted much as `render` would
+/// color it in a terminal.
+pub fn put_board(r: &Render) {
+    let color = r.style().color && has_colors();
+    for (text, look) in r.pieces() {
+        let (attr, pair) = match look {
+            None => (A_NORMAL, 0),
+            Some(Look::Empty) => (A_DIM, 0),
+            Some(Look::Filled) => (A_NORMAL, 1),
+            Some(Look::Piece) => (A_BOLD, 2),
+            Some(Look::Pivot) | Some(Look::BarePivot) => (A_BOLD, 3),
+            Some(Look::Marked(Mark::Candidate)) => (A_BOLD, 4),
+            Some(Look::Marked(Mark::Path)) => (A_DIM, 5),
+            Some(Look::Marked(Mark::Clear)) => (A_BOLD | A_UNDERLINE, 6),
+            Some(Look::Marked(Mark::Selected)) => (A_REVERSE, 4),
+        };
+        put_with(if color { attr | COLOR_PAIR(pair) } else { attr }, &text);
+    }
+}
+
+/// Ask for a line of text on the bottom line of the screen.
+pub fn prompt(question: &str) -> String {
+    mv(LINES() - 1, 0);
+    clrtoeol();
+    put(question);
+    echo();
+    curs_set(CURSOR_VISIBILITY::CURSOR_VISIBLE);
+    let mut answer = String::new();
+    getstr(&mut answer);
+    noecho();
+    curs_set(CURSOR_VISIBILITY::CURSOR_INVISIBLE);
+    answer
+}
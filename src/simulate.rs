@@ -14,7 +14,7 @@ pub struct Lattice {
 
 impl Lattice {
     pub fn new(x: i32, y:i32) -> Lattice {
-        Lattice{x: x, y: y}
+        Lattice{x, y}
     }
     fn rotated(&self, c: Clock) -> Lattice {
         match c {
@@ -50,7 +50,7 @@ impl From<Lattice> for Cell {
 
 impl Cell {
     fn moved(&self, c: Direction) -> Cell {
-        let mut out = self.clone();
+        let mut out = *self;
         match c {
             W => {
                 out.x -= 1;
@@ -77,10 +77,9 @@ impl Cell {
 
 impl Unit {
     fn command(&mut self, c: Command) {
-        match c {
-            Command::Move(d) => self.pivot = self.pivot.moved(d),
-            _ => (),
-        };
+        if let Command::Move(d) = c {
+            self.pivot = self.pivot.moved(d);
+        }
         match c {
             Command::Move(d) => for i in 0 .. self.members.len() {
                 self.members[i] = self.members[i].moved(d);
@@ -127,7 +126,7 @@ impl State {
             s.score = 0;
             return s;
         }
-        if s.unit_sequence.len() == 0 {
+        if s.unit_sequence.is_empty() {
             s.game_over = true;
             s.score = 0;
             return s;
@@ -189,7 +188,7 @@ impl State {
         self.score += size + 100 * (1 + ls) * ls / 2;
 
         // need to make sure new unit starts in valid place, or just end game
-        if self.unit_sequence.len() > 0 && self.unit_sequence[0].members.iter().any(|&c| self.is_invalid(c)) {
+        if !self.unit_sequence.is_empty() && self.unit_sequence[0].members.iter().any(|&c| self.is_invalid(c)) {
             self.game_over = true;
         }
     }
@@ -203,29 +202,65 @@ pub fn score_commands(cmds: &[Command], s0: &State) -> State {
     s
 }
 
-fn count_substrings(letters: &str, substr: &str) -> usize {
-    let sublen = substr.len();
-    if letters.len() < sublen {
-        return 0;
-    }
-    let max = letters.len() - sublen;
-    let mut count = 0;
-    for i in 0..max {
-        if &letters[i..i+sublen] == substr {
-            count += 1;
+/// How a sequence of commands ended, as found by `replay`.
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
+pub enum Ending {
+    /// Every command was used, and none was an error.
+    Clean,
+    /// The command at this index revisited an earlier position.
+    Revisit(usize),
+    /// The game was already over before the command at this index.
+    Trailing(usize),
+}
+
+/// Apply `cmds` to `s0` like `score_commands`, but also work out why
+/// the game ended if it ended badly.  Either way the score is zero,
+/// but it is nice to know why.
+pub fn replay(cmds: &[Command], s0: &State) -> (State, Ending) {
+    let mut s = s0.clone();
+    for (i, c) in cmds.iter().enumerate() {
+        if s.game_over || s.unit_sequence.is_empty() {
+            return (s.apply(*c), Ending::Trailing(i));
+        }
+        let num_units = s.unit_sequence.len();
+        s = s.apply(*c);
+        if s.game_over && s.score == 0 && s.unit_sequence.len() == num_units {
+            return (s, Ending::Revisit(i));
         }
     }
-    count
+    (s, Ending::Clean)
+}
+
+/// `c` in lower case, as phrases of power are matched.  Always one
+/// character, so positions in a lowercased solution are positions in
+/// the solution, even where lowercasing would make more.
+pub fn lower_case(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// How many times `substr` appears in `letters`, overlaps included and
+/// case ignored.  This used to miss an occurrence at the very end, so
+/// scores archived before that was fixed can be low by a phrase or
+/// two; `--verify` rescores them.
+pub fn count_substrings(letters: &str, substr: &str) -> usize {
+    let letters: Vec<char> = letters.chars().map(lower_case).collect();
+    let substr: Vec<char> = substr.chars().map(lower_case).collect();
+    if substr.is_empty() || letters.len() < substr.len() {
+        return 0;
+    }
+    letters.windows(substr.len()).filter(|w| *w == &substr[..]).count()
+}
+
+/// What saying `phrase` `reps` times is worth.
+pub fn phrase_score(phrase: &str, reps: usize) -> Score {
+    let power_bonus = if reps > 0 { 300 } else { 0 };
+    (2 * phrase.chars().count() * reps + power_bonus) as Score
 }
 
 pub fn score_pop(letters: &str, pop: &[String]) -> Score {
     let mut score = 0;
-    for p in 0 .. pop.len() {
-        let len_p = pop[p].len();
-        let reps_p = count_substrings(letters, &pop[p]);
-        let power_bonus_p = if reps_p > 0 { 300 } else { 0 };
-        let power_score_p = 2 * len_p * reps_p + power_bonus_p;
-        score += power_score_p as Score;
+    for p in pop {
+        score += phrase_score(p, count_substrings(letters, p));
     }
     score
 }
@@ -233,8 +268,6 @@ pub fn score_pop(letters: &str, pop: &[String]) -> Score {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use super::super::*;
-    use Direction::*;
 
     #[test]
     fn to_from_lattice() {
@@ -246,7 +279,7 @@ mod tests {
 
     #[test]
     fn game_loss_on_revisit() {
-        println!("");
+        println!();
         let mut s0 = State::new();
         let u = Unit{ members: vec![Cell{ x: 4, y: 5 },Cell{ x: 5, y: 5 }],
                       pivot: Cell{ x: 4, y: 5}
@@ -258,7 +291,7 @@ mod tests {
         println!("{}", s.visualize());
         s = s.apply(Command::Move(E));
         println!("{}", s.visualize());
-        assert_eq!(true, s.game_over);
+        assert!(s.game_over);
         assert_eq!(0, s.score);
 
         s = s0.clone();
@@ -268,23 +301,23 @@ mod tests {
         println!("{}", s.visualize());
         s = s.apply(Command::Rotate(Clock::Wise));
         println!("{}", s.visualize());
-        assert_eq!(false, s.game_over);
+        assert!(!s.game_over);
         assert_eq!(100, s.score);
         s = s.apply(Command::Rotate(Clock::Wise));
         println!("{}", s.visualize());
-        assert_eq!(false, s.game_over);
+        assert!(!s.game_over);
         assert_eq!(100, s.score);
         s = s.apply(Command::Rotate(Clock::Wise));
         println!("{}", s.visualize());
-        assert_eq!(false, s.game_over);
+        assert!(!s.game_over);
         assert_eq!(100, s.score);
         s = s.apply(Command::Rotate(Clock::Wise));
         println!("{}", s.visualize());
-        assert_eq!(false, s.game_over);
+        assert!(!s.game_over);
         assert_eq!(100, s.score);
         s = s.apply(Command::Rotate(Clock::Wise));
         println!("{}", s.visualize());
-        assert_eq!(true, s.game_over);
+        assert!(s.game_over);
         assert_eq!(0, s.score);
 
         s = s0.clone();
@@ -294,23 +327,23 @@ mod tests {
         println!("{}", s.visualize());
         s = s.apply(Command::Rotate(Clock::Counter));
         println!("{}", s.visualize());
-        assert_eq!(false, s.game_over);
+        assert!(!s.game_over);
         assert_eq!(100, s.score);
         s = s.apply(Command::Rotate(Clock::Counter));
         println!("{}", s.visualize());
-        assert_eq!(false, s.game_over);
+        assert!(!s.game_over);
         assert_eq!(100, s.score);
         s = s.apply(Command::Rotate(Clock::Counter));
         println!("{}", s.visualize());
-        assert_eq!(false, s.game_over);
+        assert!(!s.game_over);
         assert_eq!(100, s.score);
         s = s.apply(Command::Rotate(Clock::Counter));
         println!("{}", s.visualize());
-        assert_eq!(false, s.game_over);
+        assert!(!s.game_over);
         assert_eq!(100, s.score);
         s = s.apply(Command::Rotate(Clock::Counter));
         println!("{}", s.visualize());
-        assert_eq!(true, s.game_over);
+        assert!(s.game_over);
         assert_eq!(0, s.score);
 
 
@@ -321,34 +354,34 @@ mod tests {
         println!("{}", s.visualize());
         s = s.apply(Command::Move(W));
         println!("{}", s.visualize());
-        assert_eq!(false, s.game_over);
+        assert!(!s.game_over);
         assert_eq!(100, s.score);
         s = s.apply(Command::Move(W));
         println!("{}", s.visualize());
-        assert_eq!(false, s.game_over);
+        assert!(!s.game_over);
         assert_eq!(100, s.score);
         s = s.apply(Command::Rotate(Clock::Counter));
         println!("{}", s.visualize());
-        assert_eq!(false, s.game_over);
+        assert!(!s.game_over);
         assert_eq!(100, s.score);
         s = s.apply(Command::Move(E));
         println!("{}", s.visualize());
-        assert_eq!(false, s.game_over);
+        assert!(!s.game_over);
         assert_eq!(100, s.score);
         s = s.apply(Command::Move(E));
         println!("{}", s.visualize());
-        assert_eq!(false, s.game_over);
+        assert!(!s.game_over);
         assert_eq!(100, s.score);
         s = s.apply(Command::Rotate(Clock::Wise));
         println!("{}", s.visualize());
-        assert_eq!(true, s.game_over);
+        assert!(s.game_over);
         assert_eq!(0, s.score);
 
     }
 
     #[test]
     fn game_loss_on_revisit_with_symmetry() {
-        println!("");
+        println!();
         let mut s0 = State::new();
         let u = Unit{ members: vec![Cell{ x: 3, y: 5 },Cell{ x: 4, y: 5 },Cell{ x: 5, y: 5 }],
                       pivot: Cell{ x: 4, y: 5}
@@ -364,11 +397,11 @@ mod tests {
         println!("{}", s.visualize());
         s = s.apply(Command::Rotate(Clock::Wise));
         println!("{}", s.visualize());
-        assert_eq!(false, s.game_over);
+        assert!(!s.game_over);
         assert_eq!(100, s.score);
         s = s.apply(Command::Rotate(Clock::Wise));
         println!("{}", s.visualize());
-        assert_eq!(true, s.game_over);
+        assert!(s.game_over);
         assert_eq!(0, s.score);
 
         s = s0.clone();
@@ -378,11 +411,11 @@ mod tests {
         println!("{}", s.visualize());
         s = s.apply(Command::Rotate(Clock::Counter));
         println!("{}", s.visualize());
-        assert_eq!(false, s.game_over);
+        assert!(!s.game_over);
         assert_eq!(100, s.score);
         s = s.apply(Command::Rotate(Clock::Counter));
         println!("{}", s.visualize());
-        assert_eq!(true, s.game_over);
+        assert!(s.game_over);
         assert_eq!(0, s.score);
 
     }
@@ -397,7 +430,7 @@ mod tests {
         s0.unit_sequence.push(u);
         let s0 = s0; // mark s0 immutable now for clarity.
         let mut s = s0.apply(Command::Move(W));
-        assert_eq!(false, s.game_over);
+        assert!(!s.game_over);
         assert_eq!(0, s.score);
         assert_eq!(4, s.unit_sequence[0].members[0].x);
         assert_eq!(4, s.unit_sequence[0].pivot.x);
@@ -407,11 +440,11 @@ mod tests {
         s = s0.apply(Command::Move(W)).apply(Command::Move(W))
             .apply(Command::Move(W)).apply(Command::Move(W)).apply(Command::Move(W))
             .apply(Command::Move(W)).apply(Command::Move(W)).apply(Command::Move(W));
-        assert_eq!(true, s.game_over);
+        assert!(s.game_over);
         assert_eq!(0, s.score);
 
         s = s0.apply(Command::Move(E));
-        assert_eq!(false, s.game_over);
+        assert!(!s.game_over);
         assert_eq!(0, s.score);
         assert_eq!(6, s.unit_sequence[0].members[0].x);
         assert_eq!(6, s.unit_sequence[0].pivot.x);
@@ -421,7 +454,7 @@ mod tests {
         s = s0.apply(Command::Move(E)).apply(Command::Move(E))
             .apply(Command::Move(E)).apply(Command::Move(E)).apply(Command::Move(E))
             .apply(Command::Move(E)).apply(Command::Move(E)).apply(Command::Move(E));
-        assert_eq!(true, s.game_over);
+        assert!(s.game_over);
         assert_eq!(0, s.score);
     }
 
@@ -473,9 +506,41 @@ mod tests {
         assert_eq!(s0.score, 61);
     }
 
+    #[test]
+    fn replay_finds_bad_endings() {
+        let mut s0 = State::new();
+        s0.unit_sequence.push(Unit{ members: vec![Cell{ x: 5, y: 5 }],
+                                    pivot: Cell{ x: 5, y: 5} });
+        let (s, ending) = replay(&string_to_commands("pb"), &s0);
+        assert_eq!(ending, Ending::Revisit(1));
+        assert_eq!(s.score, 0);
+
+        let (s, ending) = replay(&string_to_commands("lllll"), &s0);
+        assert_eq!(ending, Ending::Clean);
+        assert_eq!(s.score, 1);
+
+        let (s, ending) = replay(&string_to_commands("lllllp"), &s0);
+        assert_eq!(ending, Ending::Trailing(5));
+        assert_eq!(s.score, 0);
+    }
+
+    #[test]
+    fn counts_phrases_at_the_end() {
+        assert_eq!(count_substrings("ei!", "ei!"), 1);
+        assert_eq!(count_substrings("lei!", "ei!"), 1);
+        assert_eq!(count_substrings("ei!lei!", "ei!"), 2);
+        assert_eq!(count_substrings("aaa", "aa"), 2);
+        assert_eq!(count_substrings("e", "ei!"), 0);
+        assert_eq!(score_pop("ei!", &["ei!".into()]), 306);
+        // Case doesn't matter, and commands can be more than a byte.
+        assert_eq!(count_substrings("EI!lei!", "ei!"), 2);
+        assert_eq!(count_substrings("\u{212a}ei!", "kei!"), 1);
+        assert_eq!(score_pop("İei!", &["ei!".into()]), 306);
+    }
+
     #[test]
     fn view_boards() {
-        for i in (0..25) {
+        for i in 0..25 {
             let states = input_to_states(&Input::from_json(format!("problems/problem_{}.json", i)));
             println!("Problem {}:", i);
             println!("{}", states[0].visualize());
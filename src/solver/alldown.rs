@@ -0,0 +1,46 @@
+use super::*;
+
+/// Just keep moving SE and SW alternately until the game ends.
+pub struct AllDown;
+
+impl Solve for AllDown {
+    fn name(&self) -> &'static str { "alldown" }
+    fn description(&self) -> &'static str { "alternate SE and SW moves until the game ends" }
+
+    fn solve(&self, state: &State, input: &Input, opt: &DavarOptions) -> (Solution, Score) {
+        let mut cmds: Vec<Command> = Vec::new();
+        let mut s = state.clone();
+        while !s.game_over {
+            for &cmd in [Move(SE), Move(SW)].iter() {
+                if !s.game_over {
+                    s = s.apply(cmd);
+                    cmds.push(cmd);
+                }
+            }
+        }
+        // println!("Solution[{},{}]: {}", i, s.seed, commands_to_string(cmds.clone()));
+        // println!("score[{},{}]: {}", i, s.seed, s.score);
+
+        self.finish(input, s.seed, commands_to_string(cmds), s.score, opt)
+    }
+}
+
+/// Just keep moving SE until the game ends.
+pub struct SolverSE;
+
+impl Solve for SolverSE {
+    fn name(&self) -> &'static str { "se" }
+    fn description(&self) -> &'static str { "move SE until the game ends" }
+
+    fn solve(&self, state: &State, input: &Input, opt: &DavarOptions) -> (Solution, Score) {
+        let mut cmds: Vec<Command> = Vec::new();
+        let mut s = state.clone();
+
+        while !s.game_over {
+            s = s.apply(Move(SE));
+            cmds.push(Move(SE));
+        }
+
+        self.finish(input, s.seed, commands_to_string(cmds), s.score, opt)
+    }
+}
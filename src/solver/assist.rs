@@ -0,0 +1,94 @@
+//! Where the current piece could go, for playing by hand with the
+//! same placement search the solvers use.
+
+use super::*;
+
+/// A resting position the current piece can reach, and how.
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct Placement {
+    /// The piece, where it locks.
+    pub unit: Unit,
+    /// The letters that take it there and lock it.
+    pub path: String,
+    /// The state once it is locked.
+    pub state: State,
+    /// The points for locking it.
+    pub move_score: Score,
+    /// The power score `path` adds to what was played before it.
+    pub power_score: Score,
+}
+
+impl Placement {
+    pub fn score(&self) -> Score {
+        self.move_score + self.power_score
+    }
+}
+
+/// Every resting position of the current piece that we can find a
+/// path to within `seconds`, best first.  `played` is what was
+/// played so far, which phrases of power might continue.
+pub fn placements(s: &State, played: &str, phrases: &[String], seconds: f64) -> Vec<Placement> {
+    let positions = enumerate_resting_positions(s);
+    let mut opt = DavarOptions::default();
+    let power_before = simulate::score_pop(played, phrases);
+    let mut out = Vec::new();
+    for (i, u) in positions.iter().enumerate() {
+        // Share the time fairly between the positions still to try.
+        opt.time_limit = opt.time_limit - opt.time_left()
+            + (seconds - (time::precise_time_s() - opt.starting_time)) / (positions.len() - i) as f64;
+        let (path, at) = match find_path_dfs(s, u, phrases, &opt) {
+            Ok(p) => p,
+            Err(_) => continue,
+        };
+        // Lock it with whichever move scores best.
+        let mut best: Option<Placement> = None;
+        for lock in "lapbdk".chars() {
+            let locked = at.apply(char_to_command(lock).unwrap());
+            if locked.unit_sequence.len() == at.unit_sequence.len() {
+                continue;
+            }
+            let full = format!("{}{}{}", played, path, lock);
+            let p = Placement {
+                unit: u.clone(),
+                path: format!("{}{}", path, lock),
+                move_score: locked.score - s.score,
+                power_score: simulate::score_pop(&full, phrases) - power_before,
+                state: locked,
+            };
+            let better = match best {
+                Some(ref b) => p.score() > b.score(),
+                None => true,
+            };
+            if better {
+                best = Some(p);
+            }
+        }
+        if let Some(p) = best {
+            out.push(p);
+        }
+    }
+    out.sort_by_key(|p| ::std::cmp::Reverse(p.score()));
+    out
+}
+
+#[test]
+fn placements_lock_where_they_say() {
+    let s = input_to_states(&Input::from_json("problems/problem_1.json"))[0].clone();
+    let phrases = vec!["ei!".to_string()];
+    let ps = placements(&s, "", &phrases, 1.0);
+    assert!(!ps.is_empty());
+    for w in ps.windows(2) {
+        assert!(w[0].score() >= w[1].score());
+    }
+    for p in ps.iter() {
+        let mut at = s.clone();
+        for c in p.path.chars() {
+            assert_eq!(at.unit_sequence.len(), s.unit_sequence.len());
+            at = at.apply(char_to_command(c).unwrap());
+        }
+        assert_eq!(at, p.state);
+        assert_eq!(at.unit_sequence.len(), s.unit_sequence.len() - 1);
+        assert_eq!(p.move_score, at.score - s.score);
+        assert_eq!(p.power_score, simulate::score_pop(&p.path, &phrases));
+    }
+}
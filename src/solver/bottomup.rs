@@ -0,0 +1,54 @@
+use super::*;
+
+/// Place each piece in the lowest resting position that a random
+/// walk can find a path to.
+pub struct BottomUp;
+
+impl Solve for BottomUp {
+    fn name(&self) -> &'static str { "bottomup" }
+    fn description(&self) -> &'static str { "lowest resting position reachable by a random walk" }
+    fn parameters(&self) -> Vec<Parameter> {
+        vec![Parameter::new("rng_seed", "3", "seed for the random walks, instead of --rng-seed")]
+    }
+
+    fn solve(&self, state: &State, input: &Input, opt: &DavarOptions) -> (Solution, Score) {
+        let mut solution = String::new();
+        let mut s = state.clone();
+        let mut r = random_for(opt, 3, input.id, state.seed);
+        let mut moves: Vec<String> = vec!["p".into(),
+                                          "b".into(),
+                                          "d".into(),
+                                          "k".into(),
+                                          "a".into(),
+                                          "l".into()];
+        for i in 0 .. opt.phrases_of_power.len() {
+            moves.push(opt.phrases_of_power[i].clone());
+        }
+        let moves = moves;
+        let seqs: Vec<Vec<Command>> = moves.iter().map(|s| { string_to_commands(s) }).collect();
+
+        while !s.game_over {
+            let possible_next_positions = enumerate_resting_positions(&s);
+            // for i in 0 .. possible_next_positions.len() {
+            //     println!("could go to {},{}",
+            //              possible_next_positions[i].pivot.x,
+            //              possible_next_positions[i].pivot.y);
+            // }
+            if possible_next_positions.is_empty() {
+                break;
+            }
+            for u in possible_next_positions {
+                match r.find_path(&s, &u, &moves, &seqs) {
+                    None => (),
+                    Some((more_cmds, _score)) => {
+                        s = s.apply_sequence(&string_to_commands(&more_cmds));
+                        solution = solution + &more_cmds;
+                        break;
+                    }
+                }
+            }
+        }
+
+        self.finish(input, s.seed, solution, s.score, opt)
+    }
+}
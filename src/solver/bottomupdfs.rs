@@ -0,0 +1,135 @@
+use super::*;
+
+/// Place each piece in the lowest resting position that a depth
+/// first search can find a path to, preferring paths that use
+/// phrases of power.
+pub struct BottomUpDfs;
+
+impl Solve for BottomUpDfs {
+    fn name(&self) -> &'static str { "bottomupdfs" }
+    fn description(&self) -> &'static str { "lowest resting position reachable by a depth first search" }
+    fn parameters(&self) -> Vec<Parameter> {
+        vec![Parameter::new("extra_time", "1.0", "seconds to leave unused at the end")]
+    }
+
+    fn solve(&self, state: &State, input: &Input, opt: &DavarOptions) -> (Solution, Score) {
+        let extra_time = opt.parameter("extra_time", 1.0);
+
+        let mut solution = String::new();
+        let mut s = state.clone();
+
+        let mut pop_sorted = opt.phrases_of_power.clone();
+        pop_sorted.sort_by_key(|p| ::std::cmp::Reverse(p.len()));
+        // let seqs: Vec<Vec<Command>> = moves.iter().map(|s| { string_to_commands(s) }).collect();
+
+        let mut find_path_opt = opt.clone();
+
+        let time_per_piece = opt.time_left() / (s.unit_sequence.len()+2) as f64;
+
+        while !s.game_over {
+            let mut possible_next_positions = enumerate_resting_positions(&s);
+            // Keep as many of the positions (and their traces) as the
+            // -m memory limit allows, lowest first.
+            let mut held = memory::Reservation::new(memory::global());
+            let copies = if trace::global().enabled() { 2 } else { 1 };
+            let fits = possible_next_positions.iter()
+                .take_while(|u| held.grow(copies*u.approx_bytes())).count();
+            possible_next_positions.truncate(fits);
+            // for i in 0 .. possible_next_positions.len() {
+            //     println!("could go to {},{}",
+            //              possible_next_positions[i].pivot.x,
+            //              possible_next_positions[i].pivot.y);
+            // }
+            if possible_next_positions.is_empty() {
+                break;
+            }
+            let piece_started = time::precise_time_s();
+            let piece = state.unit_sequence.len() - s.unit_sequence.len();
+            let mut candidates: Vec<trace::Candidate> = if trace::global().enabled() {
+                possible_next_positions.iter().map(trace::Candidate::new).collect()
+            } else {
+                Vec::new()
+            };
+            let mut chosen = None;
+            let mut out_of_time = false;
+            for (i, u) in possible_next_positions.iter().enumerate() {
+                if opt.time_left() < 0. {
+                    // aaack, we are late!!!
+                    break;
+                }
+                let started = time::precise_time_s();
+                let pieces_left = s.unit_sequence.len() as f64;
+                find_path_opt.time_limit = opt.time_limit - (pieces_left+0.3)*time_per_piece;
+                let plain = find_path_dfs(&s, u, &[], &find_path_opt);
+                if let Some(c) = candidates.get_mut(i) {
+                    c.search = Some(searched(&plain));
+                }
+                if plain.is_ok() {
+                    // we want extra time for when we're using pops
+                    find_path_opt.time_limit = opt.time_limit - pieces_left*time_per_piece;
+                    let with_phrases = find_path_dfs(&s, u, &opt.phrases_of_power, &find_path_opt);
+                    if let Some(c) = candidates.get_mut(i) {
+                        c.phrase_search = Some(searched(&with_phrases));
+                    }
+                    if let Ok((mut more_cmds, _)) = with_phrases {
+                        more_cmds += "l";
+                        let before = s;
+                        s = before.apply_sequence(&string_to_commands(&more_cmds));
+                        solution = solution + &more_cmds;
+                        let pop_score = simulate::score_pop(&solution, &opt.phrases_of_power);
+                        self.publish(input, &solution, s.seed, s.score + pop_score, opt);
+
+                        if opt.verbose {
+                            eprintln!("Got {} to get to {},{}", more_cmds,
+                                      u.pivot.x, u.pivot.y);
+                            eprintln!("{}", render::Render::new(&s, opt.style).path(&before, &more_cmds));
+                        }
+                        if let Some(c) = candidates.get_mut(i) {
+                            c.path = Some(more_cmds);
+                            c.seconds = time::precise_time_s() - started;
+                        }
+                        chosen = Some(i);
+                        break;
+                    }
+                }
+                if let Some(c) = candidates.get_mut(i) {
+                    c.seconds = time::precise_time_s() - started;
+                }
+                if opt.time_left() < extra_time {
+                    out_of_time = true;
+                    break;
+                }
+            }
+            if trace::global().enabled() {
+                trace::global().record(&trace::Piece {
+                    solver: self.name().into(),
+                    problem_id: input.id,
+                    seed: s.seed,
+                    piece,
+                    candidates,
+                    chosen,
+                    seconds: time::precise_time_s() - piece_started,
+                    score: s.score,
+                });
+            }
+            if out_of_time {
+                break;
+            }
+        }
+
+        // fixme: Ideally we should be tracking this as we go so we can use it.
+        let pop_score = simulate::score_pop(&solution, &opt.phrases_of_power);
+        s.score += pop_score;
+
+        self.finish(input, s.seed, solution, s.score, opt)
+    }
+}
+
+/// How a search for a path went.
+fn searched<T>(r: &Result<T, NoPath>) -> trace::Search {
+    match *r {
+        Ok(_) => trace::Search::Found,
+        Err(NoPath::Exhausted) => trace::Search::NoPath,
+        Err(NoPath::TimedOut) => trace::Search::TimedOut,
+    }
+}
@@ -0,0 +1,804 @@
+extern crate num;
+
+use super::*;
+use super::Direction::*;
+use super::Command::*;
+use super::simulate::Lattice;
+use super::opts::*;
+
+use std::cmp::Reverse;
+use std::fmt;
+use std::error::Error;
+
+mod alldown;
+mod montecarlo;
+mod supplied;
+mod bottomup;
+mod bottomupdfs;
+mod portfolio;
+pub mod assist;
+
+/// A tunable parameter of a solver, set with `--param NAME=VALUE`.
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct Parameter {
+    pub name: &'static str,
+    pub default: &'static str,
+    pub description: &'static str,
+}
+
+impl Parameter {
+    pub fn new(name: &'static str, default: &'static str, description: &'static str) -> Parameter {
+        Parameter { name, default, description }
+    }
+}
+
+/// A strategy for playing a single (problem, seed).  Each one lives
+/// in its own module and is listed in `registry`.
+pub trait Solve: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn description(&self) -> &'static str;
+    fn parameters(&self) -> Vec<Parameter> {
+        Vec::new()
+    }
+    /// The other solvers we run, as our `--param`s choose them.
+    fn members(&self, _opt: &DavarOptions) -> Result<Vec<Box<dyn Solve>>, UnknownSolver> {
+        Ok(Vec::new())
+    }
+    fn solve(&self, state: &State, input: &Input, opt: &DavarOptions) -> (Solution, Score);
+
+    /// Build the result for `cmds`, tagged with our name unless the
+    /// user gave a `--tag`.
+    fn finish(&self, input: &Input, seed: i32, cmds: String, score: Score, opt: &DavarOptions)
+              -> (Solution, Score) {
+        (Solution {
+            problem_id: input.id,
+            seed,
+            tag: match opt.tag {
+                None => Some(format!("{}[{},{}] = {}", self.name(),
+                                     input.id, seed, score)),
+                ref tag => tag.clone(),
+            },
+            solution: cmds,
+        }, score)
+    }
+
+    /// Publish a partial solution to the best-so-far store, so it can
+    /// be output if we run out of time before finishing.
+    fn publish(&self, input: &Input, cmds: &str, seed: i32, score: Score, opt: &DavarOptions) {
+        let (sol, sc) = self.finish(input, seed, cmds.into(), score, opt);
+        anytime::global().publish(sol, sc);
+    }
+}
+
+/// Every solver we know about, in the order `--help` lists them.
+pub fn registry() -> Vec<Box<dyn Solve>> {
+    vec![Box::new(alldown::AllDown),
+         Box::new(alldown::SolverSE),
+         Box::new(montecarlo::MonteCarlo),
+         Box::new(supplied::Supplied),
+         Box::new(bottomup::BottomUp),
+         Box::new(bottomupdfs::BottomUpDfs),
+         Box::new(portfolio::Portfolio)]
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct UnknownSolver(pub String);
+
+impl fmt::Display for UnknownSolver {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names: Vec<&str> = registry().iter().map(|s| s.name()).collect();
+        write!(f, "unknown solver \"{}\" (try one of: {})", self.0, names.join(", "))
+    }
+}
+
+impl Error for UnknownSolver {}
+
+pub fn name_to_solver(name: &str) -> Result<Box<dyn Solve>, UnknownSolver> {
+    for s in registry().into_iter() {
+        if s.name() == name {
+            return Ok(s);
+        }
+    }
+    Err(UnknownSolver(name.into()))
+}
+
+/// A `--param` that none of the solvers we run take.
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct UnknownParameter {
+    pub name: String,
+    pub solver: String,
+    /// The parameters we do take.
+    pub known: Vec<&'static str>,
+}
+
+impl fmt::Display for UnknownParameter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "solver \"{}\" has no parameter \"{}\"", self.solver, self.name)?;
+        if self.known.is_empty() {
+            write!(f, " (it takes none)")
+        } else {
+            write!(f, " (try one of: {})", self.known.join(", "))
+        }
+    }
+}
+
+impl Error for UnknownParameter {}
+
+/// The solver `--solver` names, once we know it, every solver it runs
+/// and every `--param` exist, so that a typo stops us before any
+/// solving starts.
+pub fn configure(opt: &DavarOptions) -> Result<Box<dyn Solve>, Box<dyn Error>> {
+    let solver = name_to_solver(&opt.solver)?;
+    let mut known: Vec<&'static str> = solver.parameters().iter().map(|p| p.name).collect();
+    for m in solver.members(opt)? {
+        for p in m.parameters() {
+            if !known.contains(&p.name) {
+                known.push(p.name);
+            }
+        }
+    }
+    for (name, _) in opt.parameters.iter() {
+        if !known.contains(&&name[..]) {
+            return Err(Box::new(UnknownParameter {
+                name: name.clone(),
+                solver: opt.solver.clone(),
+                known,
+            }));
+        }
+    }
+    Ok(solver)
+}
+
+#[test]
+fn configure_checks_names() {
+    let opt = |solver: &str, params: &[(&str, &str)]| DavarOptions {
+        solver: solver.into(),
+        parameters: params.iter().map(|&(n, v)| (n.to_string(), v.to_string())).collect(),
+        ..DavarOptions::default()
+    };
+    let error = |o: DavarOptions| configure(&o).err().map(|e| e.to_string());
+    assert_eq!(configure(&opt("mc", &[("max_commands", "5")])).ok().map(|s| s.name()), Some("mc"));
+    assert_eq!(error(opt("nc", &[])), Some(UnknownSolver("nc".into()).to_string()));
+    assert!(configure(&opt("portfolio", &[("solvers", "bottomupdfs,mc"), ("extra_time", "2"),
+                                          ("max_commands", "5")])).is_ok());
+    assert_eq!(error(opt("portfolio", &[("solvers", "mc,bottomupfds")])),
+               Some(UnknownSolver("bottomupfds".into()).to_string()));
+    assert_eq!(error(opt("bottomupdfs", &[("extra_tiem", "2")])),
+               Some("solver \"bottomupdfs\" has no parameter \"extra_tiem\" (try one of: extra_time)".into()));
+    assert_eq!(error(opt("portfolio", &[("solvers", "alldown"), ("extra_time", "2")])),
+               Some("solver \"portfolio\" has no parameter \"extra_time\" (try one of: solvers)".into()));
+    assert!(error(opt("alldown", &[("depth", "5")])).unwrap().ends_with("(it takes none)"));
+}
+
+#[test]
+fn portfolio_keeps_the_winners_tag() {
+    let input = Input::from_json("problems/problem_0.json");
+    let s = input_to_states(&input).remove(0);
+    let opt = DavarOptions {
+        parameters: vec![("solvers".to_string(), "alldown".to_string())],
+        ..DavarOptions::default()
+    };
+    let (sol, _) = portfolio::Portfolio.solve(&s, &input, &opt);
+    assert!(sol.tag.unwrap().starts_with("alldown["));
+}
+
+/// A description of every solver and its parameters, for `--help`.
+pub fn describe_solvers() -> String {
+    let mut out = String::from("Solvers:\n");
+    for s in registry() {
+        out.push_str(&format!("    {:<12} {}\n", s.name(), s.description()));
+        for p in s.parameters() {
+            out.push_str(&format!("        --param {}={}  {}\n", p.name, p.default, p.description));
+        }
+    }
+    out
+}
+
+/// Random numbers from a PCG32 generator (see pcg-random.org), whose
+/// low bits are as good as its high ones.  A generator has one of
+/// 2^63 streams, so that different jobs and threads each get random
+/// numbers of their own from the same seed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Random {
+    state: u64,
+    /// Which stream we are on; always odd.
+    inc: u64,
+}
+
+/// The random numbers for a solver on one (problem, seed).  Each
+/// gets its own stream of `--rng-seed` (or the solver's `rng_seed`
+/// parameter, or `default`), so a run does the same however its jobs
+/// are spread over threads.
+pub fn random_for(opt: &DavarOptions, default: u64, problem_id: i32, seed: i32) -> Random {
+    let rng_seed = opt.parameter("rng_seed", opt.rng_seed.unwrap_or(default));
+    Random::stream(rng_seed, ((problem_id as u32 as u64) << 32) | seed as u32 as u64)
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Random {
+        Random::stream(seed, 0)
+    }
+    /// Seeded the way the reference implementation's
+    /// `pcg32_srandom_r` is.
+    pub fn stream(seed: u64, stream: u64) -> Random {
+        let mut r = Random { state: 0, inc: (stream << 1) | 1 };
+        r.next_u32();
+        r.state = r.state.wrapping_add(seed);
+        r.next_u32();
+        r
+    }
+    /// A new generator on a stream of its own, e.g. for another
+    /// thread.
+    pub fn split(&mut self) -> Random {
+        let seed = self.next_u64();
+        let stream = self.next_u64();
+        Random::stream(seed, stream)
+    }
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old.wrapping_mul(6364136223846793005).wrapping_add(self.inc);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        xorshifted.rotate_right((old >> 59) as u32)
+    }
+    pub fn next_u64(&mut self) -> u64 {
+        ((self.next_u32() as u64) << 32) | self.next_u32() as u64
+    }
+    pub fn random(&mut self) -> usize {
+        self.next_u64() as usize
+    }
+    /// A number from 0 to `n - 1`, each as likely as the others,
+    /// which `random() % n` is not quite.
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "no numbers below 0");
+        let n = n as u64;
+        // The biggest multiple of n we can have; we try again above it.
+        let zone = u64::MAX - u64::MAX % n;
+        loop {
+            let x = self.next_u64();
+            if x < zone {
+                return (x % n) as usize;
+            }
+        }
+    }
+    pub fn commands(&mut self, s: &State, options: &[String], cmds: &[Vec<Command>])
+                    -> (String, State) {
+        if s.game_over {
+            return ("".into(), s.clone())
+        }
+        let mut handled = vec![false; options.len()];
+        let mut i = self.below(options.len());
+        let mut attempts = 0;
+        loop {
+            handled[i] = true;
+            let o = options[i].clone();
+            let ss = s.apply_sequence(&cmds[i]);
+            if false {
+                println!("attempt {} \"{}\" -> {} <{}>", attempts, o, ss.score, ss.game_over);
+            }
+            if !ss.game_over || ss.score > 0 {
+                return (o, ss)
+            }
+            // We got a zero-point illegal move, so let us try again!
+            if handled.iter().all(|&h| h) {
+                return ("".into(), s.clone());
+            }
+            while handled[i] {
+                i = self.below(options.len());
+            }
+            attempts += 1
+        }
+    }
+    fn many_commands(&mut self, s: &State, options: &[String], cmds: &[Vec<Command>], max_cmds: usize)
+                     -> (String, State) {
+        let mut s = s.clone();
+        let mut all_cmds = String::new();
+        for _ in 0 .. max_cmds {
+            let (more, snew) = self.commands(&s, options, cmds);
+            if snew.score < s.score {
+                return (all_cmds, s);
+            }
+            all_cmds = all_cmds + &more;
+            // if snew.score != s.score {
+            //     println!("so far: {} -> {}", all_cmds, snew.score);
+            // }
+            s = snew;
+            if s.game_over {
+                return (all_cmds, s)
+            }
+        }
+        (all_cmds, s)
+    }
+
+    fn find_level(&mut self, s: &State, target: i32, options: &[String], cmds: &[Vec<Command>])
+                  -> Option<(String, State)> {
+        let mut s = s.clone();
+        let mut all_cmds = String::new();
+        let num_units = s.unit_sequence.len();
+        loop {
+            let (more, snew) = self.commands(&s, options, cmds);
+            if snew.unit_sequence.len() != num_units || snew.game_over {
+                return None;
+            }
+            all_cmds = all_cmds + &more;
+            s = snew;
+            if s.unit_sequence[0].pivot.y >= target {
+                return Some((all_cmds, s));
+            }
+        }
+    }
+
+    fn find_path(&mut self, input_s: &State, target: &Unit, options: &[String], cmds: &[Vec<Command>])
+                 -> Option<(String, State)> {
+        for _ in 0 .. 40 {
+            match self.find_path_once(input_s, target, options, cmds) {
+                None => (),
+                x => {
+                    return x;
+                },
+            }
+        }
+        None
+    }
+
+    fn find_path_once(&mut self, input_s: &State, target: &Unit, options: &[String], cmds: &[Vec<Command>])
+                      -> Option<(String, State)> {
+        let mut s = input_s.clone();
+        let mut all_cmds = String::new();
+        let mut attempts = 0;
+        let num_units = s.unit_sequence.len();
+
+        let debug_path = false;
+        let mut level = s.unit_sequence[0].pivot.y + 1;
+        // println!("starting at level {} with target level {}",
+        //          level, target.pivot.y);
+        while level <= target.pivot.y {
+            attempts += 1;
+            match self.find_level(&s, level, options, cmds) {
+                None => (),
+                Some((cmds,news)) => {
+                    all_cmds = all_cmds + &cmds;
+                    s = news;
+                    level = s.unit_sequence[0].pivot.y + 1;
+                    attempts = 0;
+                }
+            }
+            if attempts > 4*s.width {
+                if debug_path {
+                    println!("NO PATH to level {} for {},{}!",
+                             level, target.pivot.x, target.pivot.y);
+                }
+                return None;
+            }
+        }
+        if s.unit_sequence[0].pivot.y == target.pivot.y {
+            for _ in 0..4*s.width {
+                if s.unit_sequence[0] == *target {
+                    println!("Found a path to target at {}, {} from {},{}! ({} left)",
+                             target.pivot.x, target.pivot.y,
+                             s.unit_sequence[0].pivot.x,
+                             s.unit_sequence[0].pivot.y,
+                             s.unit_sequence.len());
+                    for _ in 0..6 {
+                        let (more, snew) = self.commands(&s, &options[4..6], &cmds[4..6]);
+                        if snew.unit_sequence.len() != num_units {
+                            // println!("Found the finisher");
+                            println!("{}", snew.visualize());
+                            return Some((all_cmds + &more, snew));
+                        }
+                    }
+                }
+                let (more, snew) = self.commands(&s, &options[0..4], &cmds[0..4]);
+                if snew.unit_sequence.len() != num_units {
+                    continue;
+                }
+                all_cmds = all_cmds + &more;
+                s = snew;
+            }
+        } else {
+            if debug_path {
+                println!("NO PATH to target: got wrong level {} for {}, {}!",
+                         s.unit_sequence[0].pivot.y,
+                         target.pivot.x, target.pivot.y);
+                }
+        }
+        if debug_path {
+            println!("NO PATH to target at {}, {}!",
+                     target.pivot.x, target.pivot.y);
+        }
+        None
+                      }
+    // fn look_ahead_mc(&self, state: &State, route_so_far: &String, remaining_depth: u8,
+    //                  options: &[String], cmds: &[Vec<Command>]) -> (String, State) {
+
+    //     let possible_positions = enumerate_resting_positions(&state);
+    //     let mut routes_and_states = possible_positions.iter()
+    //         .filter_map(|u| self.find_path(&state, u, options, cmds));
+
+    //     if remaining_depth == 0 || state.game_over {
+    //         if let Some((mut route, mut final_state)) = routes_and_states.next() {
+    //             for (r, s) in routes_and_states {
+    //                 if s.score > state.score {
+    //                     route = r;
+    //                     final_state = s;
+    //                 }
+    //             }
+    //             return (format!("{}{}", route_so_far, route), final_state);
+    //         } else {
+    //             // no valid states found? This shouldn't happen.
+    //             return (("".into(), state.clone()));
+    //         }
+    //     } else {
+    //         let mut better_routes_and_states = routes_and_states.map(|(r, s)| {
+    //             let route_to_use = format!("{}{}", route_so_far, r);
+    //             self.look_ahead_mc(&s, &route_to_use, remaining_depth-1, options, cmds)
+    //         });
+    //         if let Some((mut route, mut final_state)) = better_routes_and_states.next() {
+    //             for (r, s) in better_routes_and_states {
+    //                 if s.score > state.score {
+    //                     route = r;
+    //                     final_state = s;
+    //                 }
+    //             }
+    //             return (format!("{}{}", route_so_far, route), final_state);
+    //         } else {
+    //             // no valid states found? This shouldn't happen.
+    //             return (("".into(), state.clone()));
+    //         }
+    //     }
+    // }
+}
+
+fn get_score(s: &State, goal: &Unit, move_string: &str) -> i32 {         // Return how much closer the move gets you
+    //println!("finding distance for {}", move_string);
+    let mut s0 = s.clone();
+    let start_dist = distance(s0.unit_sequence[0].pivot, goal.pivot);
+    //println!("Before move looks like: \n{}", s0.visualize());
+    let num_units = s.unit_sequence.len();
+
+    for cmd in string_to_commands(move_string) {
+        s0 = s0.apply(cmd);
+        //println!("After a move: \n{}", s0.visualize());
+        if s0.game_over || s0.unit_sequence.len() != num_units {
+            //println!("Blah. That move caused an invalid state.");
+            return i32::MIN         // Game-ending move. Return lowest weight possible.
+        }
+    }
+
+    //println!("for {} found score to be: {}", move_string, start_dist - distance(s0.unit_sequence[0].pivot, goal.pivot));
+    start_dist - distance(s0.unit_sequence[0].pivot, goal.pivot)
+}
+
+fn get_move_ranking_dfs(s: &State, goal: &Unit, pop: &[String], moves: &[String]) -> Option<Vec<String>> {
+    let mut recommended_moves: Vec<String> = Vec::new();        // Order moves powerwords first by distance-minimizing
+
+    let mut pop_cpy: Vec<String> = pop.to_vec();  // Order phrases by distance-minimizing
+    pop_cpy.sort_by_key(|a| Reverse(get_score(s, goal, a)));
+
+    // pop_cpy.filter(|&phrase| get_score(s, goal, &phrase) >= 0)
+    for phrase in pop_cpy {
+        if get_score(s, goal, &phrase) >= -1 {           // If it gets you closer, add it to moves
+            recommended_moves.push(phrase.clone());
+        }
+    }
+
+    let mut moves_cpy: Vec<String> = moves.to_vec();  // Then order moves by distance-minimizing
+    moves_cpy.sort_by_key(|a| Reverse(get_score(s, goal, a)));
+    for mov_str in moves_cpy {
+        if get_score(s, goal, &mov_str) >= -1 {
+            recommended_moves.push(mov_str.clone());
+        }
+    }
+    //println!("recommended moves: {:?}", recommended_moves);
+    if !recommended_moves.is_empty() {
+        Some(recommended_moves)
+    } else {
+        None
+    }
+}
+
+/// Why `find_path_dfs` came back without a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoPath {
+    /// Every path was tried.
+    Exhausted,
+    /// We ran out of time first.
+    TimedOut,
+}
+
+pub fn find_path_dfs(s: &State, goal_unit: &Unit, pop: &[String],
+                     opt: &DavarOptions) -> Result<(String, State), NoPath> {
+
+    let mut state = s.clone();
+    let mut out_cmd_stack: Vec<String> = Vec::new();
+
+    let moves: Vec<String> = vec!["p".into(), "b".into(), "a".into(), "l".into(), "d".into(), "k".into()];
+
+    // Both of these are charged against the -m memory limit.  If the
+    // budget runs out, we prune rather than expand.
+    let budget = memory::global();
+    let mut dfs_stack: memory::TrackedStack<(State, usize)> = memory::TrackedStack::new(budget);
+    let mut cur_move_idx: usize = 0;
+
+    //println!("Finding Nemo!");
+
+    let mut units_moved_down_to: memory::TrackedStack<Unit> = memory::TrackedStack::new(budget);
+    // Every state on the stack is the one we started from with the
+    // piece moved, plus a visited unit for each letter played, so we
+    // size them up once instead of walking each one.
+    let state_bytes = s.approx_bytes();
+    let unit_bytes = goal_unit.approx_bytes();
+    let mut letters: usize = 0;
+    loop {
+        //println!("entered first loop. len: {}", dfs_stack.len());
+        while let Some(next_moves) = get_move_ranking_dfs(&state, goal_unit, pop, &moves) {
+            //println!("entered second loop");
+
+            if cur_move_idx >= next_moves.len() {
+                break;
+            }
+
+            let bytes = state_bytes + letters*unit_bytes;
+            if dfs_stack.push( (state.clone(), cur_move_idx), bytes ).is_err() {
+                // Out of memory for this branch, so treat it as a dead end.
+                break;
+            }
+            letters += next_moves[cur_move_idx].len();
+            out_cmd_stack.push(next_moves[cur_move_idx].clone());
+
+            let commands = string_to_commands(&next_moves[cur_move_idx]);
+            state = state.apply_sequence(&commands);
+            match commands[commands.len()-1] {
+                Move(SW) | Move(SE) | Rotate(_) => {
+                    if units_moved_down_to.contains(&state.unit_sequence[0]) {
+                        // println!("We saved some time at {},{} ({} explored) {}",
+                        //          state.unit_sequence[0].pivot.x,
+                        //          state.unit_sequence[0].pivot.y,
+                        //          units_moved_down_to.len(),
+                        //          out_cmd_stack.connect(""));
+                        break;
+                    }
+                    // println!("We found a new thing at level {}",
+                    //          state.unit_sequence[0].pivot.y);
+                    // If we cannot afford to remember this unit, we
+                    // just lose the chance to skip it next time.
+                    let _ = units_moved_down_to.push(state.unit_sequence[0].clone(), unit_bytes);
+                },
+                _ => (),
+            }
+
+            cur_move_idx = 0;
+
+            // win!
+            // fixme: This will succeed even if we don't have correct rotation. CHECK ROTATION.
+            if state.unit_sequence[0] == *goal_unit {
+                // println!("Got {},{} using {} ({} left)", goal_unit.pivot.x,
+                //          goal_unit.pivot.y, out_cmd_stack.connect(""),
+                //          state.unit_sequence.len());
+                return Ok((out_cmd_stack.join(""), state));
+            }
+        }
+        //println!("Exited first LOOPOPOPPPPPO*******************. len: {}", dfs_stack.len());
+        // We've tried all paths and nothing works or we're out of time
+        if dfs_stack.is_empty() {
+            return Err(NoPath::Exhausted);
+        }
+        if opt.time_left() < 0.0 {
+            return Err(NoPath::TimedOut);
+        }
+        //println!("Backtracking.");
+        let (old_state, old_move_idx) = dfs_stack.pop().unwrap();
+        state = old_state;
+        cur_move_idx = old_move_idx + 1;
+        letters -= out_cmd_stack.pop().map_or(0, |m| m.len());
+    }
+}
+
+// pub fn find_path(s: &State, goal: &Unit) -> Option(&[Commands]) {
+//     let mut s = s.clone();
+//     let mut all_cmds = String::new();
+//     for _ in 0 .. max_cmds {
+//         let (more, snew) = self.commands(&s, options, cmds);
+
+//     }
+// }
+
+/// Taxicab-like distance formula for our lattice
+// 1 SE = 1
+// 1 E = 1
+// 1 SE - 1 E = 1 SW = 1
+// - 1 SE = 1
+// -1 SE + 1 E = -1 SW = 1
+fn distance(a: Cell, b: Cell) -> i32 {
+    let v: Lattice = Lattice::from(b) - Lattice::from(a);
+    if v.x.is_positive() == v.y.is_positive() {
+        v.x.abs() + v.y.abs()
+    } else {
+        ::std::cmp::max(v.x.abs(), v.y.abs())
+    }
+}
+
+#[test]
+fn find_path_says_why_it_stopped() {
+    let states = input_to_states(&Input::from_json("problems/problem_0.json"));
+    let s = &states[0];
+    let goal = enumerate_resting_positions(s)[0].clone();
+    assert!(find_path_dfs(s, &goal, &[], &DavarOptions::default()).is_ok());
+    // Pieces only fall, so there is no way up above the board.
+    let mut above = s.unit_sequence[0].clone();
+    for c in above.members.iter_mut().chain(Some(&mut above.pivot)) {
+        c.y -= 10;
+    }
+    assert_eq!(find_path_dfs(s, &above, &[], &DavarOptions::default()).err(), Some(NoPath::Exhausted));
+    let late = DavarOptions { time_limit: -1.0, ..DavarOptions::default() };
+    assert_eq!(find_path_dfs(s, &above, &[], &late).err(), Some(NoPath::TimedOut));
+}
+
+#[test]
+fn test_distance() {
+    // tuples in form (a.x, a.y, b.x, b.y, distance)
+    let tests = &[(1, 2, 0, 5, 3),
+                  (1, 7, 1, 4, 3),
+                  (2, 5, 3, 6, 1),
+                  (2, 5, 2, 6, 1),
+                  (3, 7, 3, 7, 0),
+                  (2, 5, 2, 4, 1),
+                  (2, 5, 3, 4, 1),
+                  (1, 2, 0, 4, 2),
+                  (3, 2, 4, 4, 2),
+                  ];
+    for &(ax, ay, bx, by, d) in tests {
+        println!("a: ({}, {}), b: ({}, {}), d: {}", ax, ay, bx, by, d);
+        println!("Ensuring symmetry.");
+        assert_eq!(distance(Cell{x:ax, y:ay}, Cell{x:bx, y:by}), distance(Cell{x:bx, y:by}, Cell{x:ax, y:ay}));
+        println!("Ensuring correctness.");
+        assert_eq!(distance(Cell{x:ax, y:ay}, Cell{x:bx, y:by}), d);
+    }
+}
+
+/// Every place the current piece of `state` could lock.
+pub fn enumerate_resting_positions(state: &State) -> Vec<Unit> {
+    if state.unit_sequence.is_empty() {
+        return Vec::new();
+    }
+    let unit = &state.unit_sequence[0];
+
+    let min = unit.members.iter().map(|&m| distance(unit.pivot, m)).min().unwrap();
+
+    let mut valid_positions: Vec<Unit> = Vec::new();
+
+    let mut orientations: Vec<Unit> = Vec::new();
+    {
+        let mut u = unit.clone();
+        for _ in 0..6 {
+            if !orientations.contains(&u) {
+                orientations.push(u.clone());
+            } else {
+                break;
+            }
+            u.rotate(Clock::Wise);
+        }
+    }
+    for y in (-min..state.height + min).rev() {
+        for x in -min..state.width + min {
+            let final_pivot = Cell::new(x, y);
+            let delta = Lattice::from(final_pivot) - Lattice::from(unit.pivot);
+            for u in orientations.iter() {
+                let final_members = u.members.iter().map(|&m| Cell::from(Lattice::from(m) + delta));
+                let unit = Unit{pivot: final_pivot, members: final_members.collect()};
+                if !state.is_unit_invalid(&unit) {
+                    valid_positions.push(unit);
+                }
+            }
+        }
+    }
+    // We should have all valid positions. Now let's trim them; to start, we only want
+    // ones that have either filled cells or floor below
+    let mut real_positions = Vec::with_capacity(valid_positions.len());
+    for u in valid_positions {
+        #[inline]
+        fn has_lower_neighbor(state: &State, c: Cell) -> bool {
+            state.is_filled(Cell{x: c.x, y: c.y + 1}) ||
+                if c.y % 2 == 0 {
+                    state.is_filled(Cell{x: c.x - 1, y: c.y + 1})
+                } else {
+                    state.is_filled(Cell{x: c.x + 1, y: c.y + 1})
+                }
+        }
+        if u.members.iter().any(|&c| {
+            c.y == state.height - 1 || has_lower_neighbor(state, c)
+        }) {
+            real_positions.push(u);
+        }
+    }
+
+    // let's sort them by center of mass!
+    #[inline]
+    fn center_of_mass(unit: &Unit) -> f32 {
+        let y = unit.members.iter().fold(0, |curr_y, &cell| curr_y + cell.y);
+        y as f32 / unit.members.len() as f32
+    }
+    real_positions.sort_by(|a, b| center_of_mass(b).partial_cmp(&center_of_mass(a)).unwrap_or(::std::cmp::Ordering::Equal));
+    real_positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pcg32_matches_the_reference() {
+        // From pcg32-demo.c in the reference implementation.
+        let mut r = Random::stream(42, 54);
+        let first: Vec<u32> = (0..6).map(|_| r.next_u32()).collect();
+        assert_eq!(first, vec![0xa15c02b7, 0x7b47f409, 0xba1d3330, 0x83d2f293, 0xbfa4784b, 0xcbed606e]);
+
+        // Same seed, same numbers; different stream, different ones.
+        let mut a = Random::stream(7, 1);
+        let mut b = Random::stream(7, 1);
+        let mut c = Random::stream(7, 2);
+        let xs: Vec<u64> = (0..10).map(|_| a.next_u64()).collect();
+        assert_eq!(xs, (0..10).map(|_| b.next_u64()).collect::<Vec<_>>());
+        assert!(xs != (0..10).map(|_| c.next_u64()).collect::<Vec<_>>());
+        let mut d = a.split();
+        assert!(d != a && d.next_u64() != a.next_u64());
+
+        // Every number below 6 turns up about as often.
+        let mut counts = [0; 6];
+        for _ in 0 .. 60000 {
+            counts[r.below(6)] += 1;
+        }
+        assert!(counts.iter().all(|&n| n > 9500 && n < 10500), "{:?}", counts);
+    }
+
+    #[test]
+    fn test_random_many_commands() {
+        let states = input_to_states(&Input::from_json("problems/problem_0.json"));
+        let s = states[0].clone();
+
+        let moves: Vec<String> = vec!["p".into(),
+                                      "b".into(),
+                                      "d".into(),
+                                      "k".into(),
+                                      "a".into(),
+                                      "l".into()];
+        let seqs: Vec<Vec<Command>> = moves.iter().map(|s| { string_to_commands(s) }).collect();
+
+        for i in 0..30 {
+            let mut r = Random::new(i);
+            let (cmds, snew) = r.many_commands(&s, &moves, &seqs, 100);
+            let alt_snew = s.apply_sequence(&string_to_commands(&cmds));
+            println!("cmds {}", cmds);
+            assert_eq!(snew.score, alt_snew.score);
+        }
+    }
+
+    #[test]
+    fn test_random_commands() {
+        let states = input_to_states(&Input::from_json("problems/problem_0.json"));
+        let mut s = states[0].clone();
+        s.score = 5;
+        let s = s;
+
+        let moves: Vec<String> = vec!["p".into(),
+                                      "b".into(),
+                                      "d".into(),
+                                      "k".into(),
+                                      "a".into(),
+                                      "l".into()];
+        let mut seqs: Vec<Vec<Command>> = Vec::new();
+        for i in 0 .. moves.len() {
+            seqs.push(string_to_commands(&moves[i]));
+            println!("hello {} -> {:?}", moves[i], seqs[i]);
+        }
+        let seqs = seqs;
+
+        for i in 0..30 {
+            let mut r = Random::new(i);
+            let (cmds, snew) = r.commands(&s, &moves, &seqs);
+            let alt_snew = s.apply_sequence(&string_to_commands(&cmds));
+            println!("cmds {}", cmds);
+            assert_eq!(snew.score, alt_snew.score);
+            assert!(snew.score >= s.score);
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use super::*;
+
+/// Repeatedly replay a random prefix of the best solution so far,
+/// finish it with random moves and phrases, and keep the result if
+/// it scores better.
+pub struct MonteCarlo;
+
+impl Solve for MonteCarlo {
+    fn name(&self) -> &'static str { "mc" }
+    fn description(&self) -> &'static str { "random playouts from random points of the best solution" }
+    fn parameters(&self) -> Vec<Parameter> {
+        vec![Parameter::new("rng_seed", "5", "seed for the random moves, instead of --rng-seed"),
+             Parameter::new("max_commands", "10000", "longest random playout, in moves and phrases")]
+    }
+
+    fn solve(&self, state: &State, input: &Input, opt: &DavarOptions) -> (Solution, Score) {
+        let mut r = random_for(opt, 5, input.id, state.seed);
+        let max_commands = opt.parameter("max_commands", 10000);
+
+        let mut moves: Vec<String> = vec!["p".into(),
+                                          "b".into(),
+                                          "d".into(),
+                                          "k".into(),
+                                          "a".into(),
+                                          "l".into()];
+        for i in 0 .. opt.phrases_of_power.len() {
+            moves.push(opt.phrases_of_power[i].clone());
+        }
+        let moves = moves;
+        let seqs: Vec<Vec<Command>> = moves.iter().map(|s| { string_to_commands(s) }).collect();
+
+        let mut best_cmds: String = "".into();
+        let mut best_state = state.clone();
+        // Start from a supplied solution if we have one, e.g. from the
+        // portfolio solver.
+        if let Some(ref sol) = opt.solution {
+            let mut s = state.apply_sequence(&string_to_commands(sol));
+            if s.score > 0 {
+                s.score += simulate::score_pop(sol, &opt.phrases_of_power);
+            }
+            best_cmds = sol.clone();
+            best_state = s;
+        }
+        // The best game so far and each playout are charged against
+        // the -m memory limit.  When a playout doesn't fit, we stop
+        // and keep what we have.
+        let budget = memory::global();
+        let state_bytes = state.approx_bytes();
+        let mut best_held = memory::Reservation::new(budget);
+        best_held.grow(state_bytes + best_cmds.len());
+        let original_time_left = opt.time_left();
+        let mut iters_per_time_check = 100;
+        let mut time_per_iter;// = 1.0;
+        let time_per_check_goal = if original_time_left < 2.0 { original_time_left/20.0 } else { 0.5 };
+        for iters in 1..1000000000 {
+            let mut playout = memory::Reservation::new(budget);
+            if !playout.grow(2*state_bytes + best_cmds.len() + max_commands) {
+                break;
+            }
+            let split_point = if best_cmds.is_empty() { 0 } else { r.below(best_cmds.len()) };
+            let start: String = best_cmds[0..split_point].into();
+            let mid_state = simulate::score_commands(&string_to_commands(&start),
+                                                     state);
+            let (mut cmds, mut new_s) = r.many_commands(&mid_state, &moves, &seqs, max_commands);
+            if new_s.score > 0 {
+                cmds = start + &cmds;
+                // Only count pop_score if we have a non-zero other
+                // score, since otherwise we could accidentally count
+                // something as nonzero that actually has zero score
+                // for doing an illegal move.  Maybe this fixes bug?
+                let pop_score = simulate::score_pop(&cmds, &opt.phrases_of_power);
+                // println!("scores {} and {}", new_s.score, pop_score);
+                new_s.score += pop_score;
+            }
+            let new_s = new_s;
+            if new_s.score > best_state.score {
+                // println!("Found better score with {} > {}",
+                //          new_s.score, best_state.score);
+                best_cmds.truncate(split_point);
+                best_cmds = cmds;
+                best_state = new_s;
+                // The old best's memory goes back to the budget.
+                drop(::std::mem::replace(&mut best_held, playout));
+                self.publish(input, &best_cmds, best_state.seed, best_state.score, opt);
+            }
+            if iters % iters_per_time_check == 0 {
+                let current_time_left = opt.time_left();
+                if current_time_left < 3.0*time_per_check_goal {
+                    return self.finish(input, best_state.seed, best_cmds, best_state.score, opt);
+                }
+                time_per_iter = (original_time_left - current_time_left) / iters as f64;
+                // At least one, or short runs would check `iters % 0`.
+                iters_per_time_check = ((time_per_check_goal / time_per_iter) as usize).max(1);
+            }
+        }
+
+        self.finish(input, best_state.seed, best_cmds, best_state.score, opt)
+    }
+}
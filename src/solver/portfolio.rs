@@ -0,0 +1,52 @@
+use super::*;
+
+/// Run several other solvers on the same seed, splitting the time
+/// between them, and keep whichever scores best.  Each solver after
+/// the first is handed the best solution so far with `--solution`,
+/// which those that can use a starting point (like mc) build on.  The
+/// best solution keeps the tag of the solver that found it.
+pub struct Portfolio;
+
+impl Solve for Portfolio {
+    fn name(&self) -> &'static str { "portfolio" }
+    fn description(&self) -> &'static str { "run several solvers on each seed and keep the best" }
+    fn parameters(&self) -> Vec<Parameter> {
+        vec![Parameter::new("solvers", "bottomupdfs,mc", "comma-separated solvers to run, in order")]
+    }
+
+    fn members(&self, opt: &DavarOptions) -> Result<Vec<Box<dyn Solve>>, UnknownSolver> {
+        let names: String = opt.parameter("solvers", "bottomupdfs,mc".into());
+        names.split(',')
+            .filter(|n| *n != self.name())
+            .map(name_to_solver)
+            .collect()
+    }
+
+    fn solve(&self, state: &State, input: &Input, opt: &DavarOptions) -> (Solution, Score) {
+        // `configure` checked these names before we started.
+        let solvers = self.members(opt).unwrap_or_default();
+
+        let mut best: Option<(Solution, Score)> = None;
+        for i in 0 .. solvers.len() {
+            // Give each remaining solver an equal share of the time left.
+            let share = opt.time_left() / (solvers.len() - i) as f64;
+            let mut sub_opt = opt.clone();
+            sub_opt.time_limit = opt.time_limit - opt.time_left() + share;
+            if let Some((ref sol, _)) = best {
+                sub_opt.solution = Some(sol.solution.clone());
+            }
+            let (sol, sc) = solvers[i].solve(state, input, &sub_opt);
+            if opt.verbose {
+                eprintln!("portfolio: {}[{},{}] = {}", solvers[i].name(), input.id, state.seed, sc);
+            }
+            best = match best {
+                Some((_, old)) if old >= sc => best,
+                _ => Some((sol, sc)),
+            };
+        }
+        match best {
+            Some(best) => best,
+            None => self.finish(input, state.seed, String::new(), 0, opt),
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use super::*;
+
+/// Instead of running an algorithm, score the `--solution` string.
+pub struct Supplied;
+
+impl Solve for Supplied {
+    fn name(&self) -> &'static str { "supplied" }
+    fn description(&self) -> &'static str { "score the string given with --solution" }
+
+    fn solve(&self, state: &State, input: &Input, opt: &DavarOptions) -> (Solution, Score) {
+        let mut s = state.clone();
+
+        let old_solution = opt.solution.clone().expect("Must enter solution to use \"supplied\" solver");
+        let mut cmds: Vec<char> = Vec::new();
+
+        for ch in old_solution.chars() {
+            let cmd = string_to_commands(&ch.to_string())[0];
+            s = s.apply(cmd);
+            cmds.push(ch);
+        }
+        self.finish(input, s.seed, cmds.into_iter().collect(), s.score, opt)
+    }
+}
@@ -0,0 +1,321 @@
+//! Ways of submitting solutions.
+//!
+//! `HttpSubmitter` posts to the contest server (or anything that
+//! looks like it) using curl.  `FileSubmitter` just writes what would
+//! be posted to a file, `DryRun` prints the whole request, and
+//! `MockServer` is a tiny local server to point `HttpSubmitter` at
+//! when testing offline.
+
+use super::*;
+
+use serde_json;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+pub const DEFAULT_URL: &str = "https://davar.icfpcontest.org/teams/97/solutions";
+
+/// Where to submit unless `--submit-to` says otherwise:
+/// `DAVAR_SUBMIT_URL` if it is set, or else the contest server.
+pub fn default_target() -> String {
+    ::std::env::var("DAVAR_SUBMIT_URL").unwrap_or(DEFAULT_URL.into())
+}
+
+/// What the server said about our submission.
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    pub fn is_success(&self) -> bool {
+        self.status >= 200 && self.status < 300
+    }
+}
+
+impl fmt::Display for Response {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Pretty print the body if it is JSON, as the server's is.
+        match serde_json::from_str::<serde_json::Value>(&self.body) {
+            Ok(j) => write!(f, "status {}: {}", self.status,
+                            serde_json::to_string_pretty(&j).unwrap()),
+            Err(_) => write!(f, "status {}: {}", self.status, self.body),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SubmitError {
+    Io(io::Error),
+    Curl(String),
+    Rejected(Response),
+    /// Submitting to a server, but we have no API token for it.
+    NoToken,
+}
+
+impl fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SubmitError::Io(ref e) => write!(f, "couldn't submit: {}", e),
+            SubmitError::Curl(ref e) => write!(f, "curl failed: {}", e),
+            SubmitError::Rejected(ref r) => write!(f, "submission rejected with {}", r),
+            SubmitError::NoToken => write!(f, "need an API token to submit to a server"),
+        }
+    }
+}
+
+impl Error for SubmitError {}
+
+impl From<io::Error> for SubmitError {
+    fn from(e: io::Error) -> SubmitError {
+        SubmitError::Io(e)
+    }
+}
+
+pub trait Submitter {
+    fn submit(&self, s: &[Solution]) -> Result<Response, SubmitError>;
+}
+
+/// Does submitting to `target` need an API token?  The mock server
+/// and files do not.
+pub fn needs_credentials(target: &str) -> bool {
+    target != "mock" && !target.starts_with("file:")
+}
+
+/// Pick a submitter from `--submit-to`: a URL, `file:PATH`, or
+/// `mock` to start a `MockServer` and post to that.  `token` is only
+/// used for URLs, which can't do without it.
+pub fn from_target(target: &str, token: Option<String>) -> Result<Box<dyn Submitter>, SubmitError> {
+    if target == "mock" {
+        let server = MockServer::start()?;
+        Ok(Box::new(HttpSubmitter { url: server.url(), token: "mock".into() }))
+    } else if let Some(path) = target.strip_prefix("file:") {
+        Ok(Box::new(FileSubmitter { path: path.into() }))
+    } else {
+        match token {
+            Some(token) => Ok(Box::new(HttpSubmitter { url: target.into(), token })),
+            None => Err(SubmitError::NoToken),
+        }
+    }
+}
+
+pub struct HttpSubmitter {
+    pub url: String,
+    pub token: String,
+}
+
+impl HttpSubmitter {
+    /// The curl command to post the file `body`.  The token is not in
+    /// it, since anyone on the machine can read a command line with
+    /// `ps`; curl reads it from `config` on stdin instead.
+    fn command(&self, body: &Path) -> process::Command {
+        let mut c = process::Command::new("curl");
+        c.arg("--silent").arg("--show-error")
+            .arg("--config").arg("-")
+            .arg("-X").arg("POST")
+            .arg("-H").arg("Content-Type: application/json")
+            .arg("--data-binary").arg(format!("@{}", body.display()))
+            .arg("--write-out").arg("\n%{http_code}")
+            .arg(&self.url);
+        c
+    }
+    /// What curl reads with `--config -`.
+    fn config(&self) -> String {
+        format!("user = \":{}\"\n", self.token.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+impl Submitter for HttpSubmitter {
+    fn submit(&self, s: &[Solution]) -> Result<Response, SubmitError> {
+        let body = ::std::env::temp_dir().join(format!("davar-submit-{}.json", process::id()));
+        File::create(&body)?.write_all(serde_json::to_string(&s).unwrap().as_bytes())?;
+        let out = self.command(&body)
+            .stdin(process::Stdio::piped())
+            .stdout(process::Stdio::piped())
+            .stderr(process::Stdio::piped())
+            .spawn()
+            .and_then(|mut child| {
+                child.stdin.take().unwrap().write_all(self.config().as_bytes())?;
+                child.wait_with_output()
+            });
+        let _ = ::std::fs::remove_file(&body);
+        let out = out?;
+        if !out.status.success() {
+            return Err(SubmitError::Curl(String::from_utf8_lossy(&out.stderr).trim().into()));
+        }
+        // We asked curl to put the status code on a line of its own
+        // after the body.
+        let stdout = String::from_utf8_lossy(&out.stdout).into_owned();
+        let (body, status) = match stdout.rfind('\n') {
+            Some(i) => (&stdout[..i], &stdout[i+1..]),
+            None => ("", &stdout[..]),
+        };
+        let r = Response {
+            status: match status.trim().parse() {
+                Ok(s) => s,
+                Err(_) => return Err(SubmitError::Curl(format!("bad status \"{}\"", status))),
+            },
+            body: body.into(),
+        };
+        if r.is_success() { Ok(r) } else { Err(SubmitError::Rejected(r)) }
+    }
+}
+
+/// Write what we would post to a file instead.
+pub struct FileSubmitter {
+    pub path: PathBuf,
+}
+
+impl Submitter for FileSubmitter {
+    fn submit(&self, s: &[Solution]) -> Result<Response, SubmitError> {
+        let mut f = File::create(&self.path)?;
+        f.write_all(serde_json::to_string(&s).unwrap().as_bytes())?;
+        Ok(Response {
+            status: 200,
+            body: format!("wrote {} solutions to {}", s.len(), self.path.display()),
+        })
+    }
+}
+
+/// Print exactly what `HttpSubmitter` would post, without posting.
+pub struct DryRun {
+    pub url: String,
+    pub token: Option<String>,
+}
+
+impl Submitter for DryRun {
+    fn submit(&self, s: &[Solution]) -> Result<Response, SubmitError> {
+        let body = serde_json::to_string(&s).unwrap();
+        println!("POST {}", self.url);
+        // Not the token itself, since this ends up in logs.
+        match self.token {
+            Some(ref t) => println!("Authorization: Basic base64(\":<{}-character token>\")", t.len()),
+            None => println!("Authorization: (no API token found)"),
+        }
+        println!("Content-Type: application/json");
+        println!("Content-Length: {}", body.len());
+        println!();
+        println!("{}", body);
+        Ok(Response { status: 200, body: format!("dry run, {} solutions not sent", s.len()) })
+    }
+}
+
+/// One request received by a `MockServer`.
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct MockRequest {
+    pub method: String,
+    pub path: String,
+    pub authorization: Option<String>,
+    pub body: String,
+}
+
+/// A local stand-in for the contest server.  It accepts a JSON array
+/// of solutions and replies like the real one would, keeping every
+/// request it gets so tests can look at them.
+pub struct MockServer {
+    port: u16,
+    pub requests: Arc<Mutex<Vec<MockRequest>>>,
+}
+
+impl MockServer {
+    pub fn start() -> io::Result<MockServer> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let port = listener.local_addr()?.port();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let reqs = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let _ = MockServer::handle(stream, &reqs);
+            }
+        });
+        Ok(MockServer { port, requests })
+    }
+    pub fn url(&self) -> String {
+        format!("http://127.0.0.1:{}/teams/97/solutions", self.port)
+    }
+    fn handle(stream: TcpStream, requests: &Mutex<Vec<MockRequest>>) -> io::Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut writer = stream;
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let words: Vec<String> = line.split_whitespace().map(|w| w.into()).collect();
+        if words.len() < 2 {
+            return Ok(());
+        }
+        let mut length = 0;
+        let mut authorization = None;
+        let mut expect_continue = false;
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header)?;
+            let header = header.trim();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(i) = header.find(':') {
+                let (name, value) = (header[..i].to_lowercase(), header[i+1..].trim());
+                match &name[..] {
+                    "content-length" => length = value.parse().unwrap_or(0),
+                    "authorization" => authorization = Some(value.into()),
+                    "expect" => expect_continue = value.to_lowercase() == "100-continue",
+                    _ => (),
+                }
+            }
+        }
+        if expect_continue {
+            writer.write_all(b"HTTP/1.1 100 Continue\r\n\r\n")?;
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body)?;
+        let body = String::from_utf8_lossy(&body).into_owned();
+
+        let (status, reply) = match serde_json::from_str(&body) {
+            Ok(serde_json::Value::Array(ref a)) if words[0] == "POST" =>
+                ("200 OK", format!("{{\"received\":{}}}", a.len())),
+            _ => ("400 Bad Request", "{\"error\":\"expected a JSON array of solutions\"}".into()),
+        };
+        requests.lock().unwrap().push(MockRequest {
+            method: words[0].clone(),
+            path: words[1].clone(),
+            authorization,
+            body,
+        });
+        write!(writer, "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+               status, reply.len(), reply)
+    }
+}
+
+#[test]
+fn submit_to_mock_server() {
+    let server = MockServer::start().unwrap();
+    let submitter = HttpSubmitter { url: server.url(), token: "sekrit".into() };
+    let sols = vec![Solution { problem_id: 1, seed: 2, tag: None, solution: "ei!".into() }];
+    let r = submitter.submit(&sols).unwrap();
+    assert_eq!(r.status, 200);
+    assert_eq!(r.body, "{\"received\":1}");
+    let reqs = server.requests.lock().unwrap();
+    assert_eq!(reqs.len(), 1);
+    assert_eq!(reqs[0].method, "POST");
+    assert_eq!(reqs[0].path, "/teams/97/solutions");
+    assert_eq!(reqs[0].authorization, Some("Basic OnNla3JpdA==".into()));
+    assert_eq!(reqs[0].body, serde_json::to_string(&sols).unwrap());
+
+    // The token must not be on curl's command line, where anyone can
+    // read it.
+    let c = submitter.command(Path::new("body.json"));
+    assert!(c.get_args().all(|a| !a.to_string_lossy().contains("sekrit")));
+    assert_eq!(submitter.config(), "user = \":sekrit\"\n");
+    let odd = HttpSubmitter { url: server.url(), token: "a\"b\\c".into() };
+    assert_eq!(odd.config(), "user = \":a\\\"b\\\\c\"\n");
+
+    // A server needs a token, but a file doesn't.
+    assert!(matches!(from_target(&server.url(), None), Err(SubmitError::NoToken)));
+    assert!(from_target("file:/dev/null", None).is_ok());
+}
@@ -0,0 +1,200 @@
+//! A solution replayed one character at a time, so that we can step
+//! back and forth through it.
+
+use super::*;
+
+pub struct Timeline {
+    pub solution: Solution,
+    chars: Vec<char>,
+    /// `states[i]` is the state after the first `i` characters.
+    states: Vec<State>,
+    phrases: Vec<String>,
+    /// The phrase (as an index into `phrases`) each character is
+    /// part of, if any.
+    phrase_at: Vec<Option<usize>>,
+    /// How the solution ended, as `--verify` sees it, but counting
+    /// characters rather than commands.
+    ending: simulate::Ending,
+}
+
+impl Timeline {
+    pub fn new(solution: &Solution, initial: &State, phrases: &[String]) -> Timeline {
+        let chars: Vec<char> = solution.solution.chars().collect();
+        // Play every character, even after the game is over, which is
+        // how the simulator scores a solution.
+        let mut states = Vec::with_capacity(chars.len() + 1);
+        states.push(initial.clone());
+        for (i, c) in chars.iter().enumerate() {
+            let s = states[i].apply_sequence(&string_to_commands(&c.to_string()));
+            states.push(s);
+        }
+        // Which character each command is.
+        let at: Vec<usize> = (0 .. chars.len()).filter(|&i| char_to_command(chars[i]).is_some()).collect();
+        let ending = match simulate::replay(&string_to_commands(&solution.solution), initial).1 {
+            simulate::Ending::Clean => simulate::Ending::Clean,
+            simulate::Ending::Revisit(i) => simulate::Ending::Revisit(at[i]),
+            simulate::Ending::Trailing(i) => simulate::Ending::Trailing(at[i]),
+        };
+        Timeline {
+            solution: solution.clone(),
+            phrase_at: find_phrases(&chars, phrases),
+            chars,
+            states,
+            phrases: phrases.to_vec(),
+            ending,
+        }
+    }
+
+    /// The number of characters in the solution.
+    pub fn len(&self) -> usize {
+        self.chars.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.chars.is_empty()
+    }
+    /// The state after the first `i` characters.
+    pub fn state(&self, i: usize) -> &State {
+        &self.states[i]
+    }
+    pub fn char_at(&self, i: usize) -> char {
+        self.chars[i]
+    }
+    pub fn move_score(&self, i: usize) -> Score {
+        self.states[i].score
+    }
+    /// The power score of the first `i` characters, which is nothing
+    /// once they include the move that ends the solution badly.
+    pub fn power_score(&self, i: usize) -> Score {
+        match self.error_at() {
+            Some(e) if i > e => 0,
+            _ => {
+                let prefix: String = self.chars[..i].iter().cloned().collect();
+                simulate::score_pop(&prefix, &self.phrases)
+            },
+        }
+    }
+    /// The phrase of power character `i` is part of.
+    pub fn phrase_at(&self, i: usize) -> Option<&str> {
+        self.phrase_at.get(i).and_then(|p| p.map(|p| &self.phrases[p][..]))
+    }
+    pub fn ending(&self) -> simulate::Ending {
+        self.ending
+    }
+    /// The character that revisits a position, or is played after
+    /// the game is over, if there is one.
+    pub fn error_at(&self) -> Option<usize> {
+        match self.ending {
+            simulate::Ending::Clean => None,
+            simulate::Ending::Revisit(i) | simulate::Ending::Trailing(i) => Some(i),
+        }
+    }
+    /// How many pieces have been locked after `i` characters.
+    pub fn piece(&self, i: usize) -> usize {
+        self.states[0].unit_sequence.len() - self.states[i].unit_sequence.len()
+    }
+    /// Where the piece being moved after `i` characters locks, or
+    /// the end of the solution.
+    pub fn next_piece(&self, i: usize) -> usize {
+        (i+1 .. self.len() + 1).find(|&j| self.piece(j) > self.piece(i)).unwrap_or(self.len())
+    }
+    /// Where the piece before the one being moved after `i`
+    /// characters locked, or the start.
+    pub fn prev_piece(&self, i: usize) -> usize {
+        if i == 0 {
+            return 0;
+        }
+        (1 .. i).rev().find(|&j| self.piece(j) > self.piece(j-1)).unwrap_or(0)
+    }
+}
+
+/// Which phrase, if any, each character is part of.  Where phrases
+/// overlap, the longer one wins.
+pub fn find_phrases(chars: &[char], phrases: &[String]) -> Vec<Option<usize>> {
+    use simulate::lower_case;
+    let lower: Vec<char> = chars.iter().map(|&c| lower_case(c)).collect();
+    let mut by_length: Vec<usize> = (0 .. phrases.len()).collect();
+    by_length.sort_by_key(|&p| ::std::cmp::Reverse(phrases[p].chars().count()));
+    let mut out = vec![None; chars.len()];
+    for p in by_length {
+        let phrase: Vec<char> = phrases[p].chars().map(lower_case).collect();
+        if phrase.is_empty() || phrase.len() > lower.len() {
+            continue;
+        }
+        for start in 0 .. lower.len() - phrase.len() + 1 {
+            if lower[start .. start + phrase.len()] == phrase[..] {
+                for c in out[start .. start + phrase.len()].iter_mut() {
+                    if c.is_none() {
+                        *c = Some(p);
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+#[test]
+fn step_through_a_solution() {
+    let initial = input_to_states(&Input::from_json("problems/problem_1.json"))[0].clone();
+    let sol = Solution { problem_id: 1, seed: 0, tag: None, solution: "ei!llllaaaalllll".into() };
+    let phrases = vec!["ei!".to_string(), "aa".to_string()];
+    let t = Timeline::new(&sol, &initial, &phrases);
+    assert_eq!(t.len(), 16);
+    assert_eq!(*t.state(0), initial);
+    let end = simulate::score_commands(&string_to_commands(&sol.solution), &initial);
+    assert_eq!(t.move_score(t.len()), end.score);
+    assert_eq!(t.phrase_at(0), Some("ei!"));
+    assert_eq!(t.phrase_at(2), Some("ei!"));
+    assert_eq!(t.phrase_at(3), None);
+    assert_eq!(t.phrase_at(7), Some("aa"));
+    assert_eq!(t.power_score(2), 0);
+    assert_eq!(t.power_score(3), 306);
+    // Every piece boundary is a place where a piece locked.
+    let mut i = 0;
+    while i < t.len() {
+        let j = t.next_piece(i);
+        assert!(j > i);
+        if j < t.len() || t.piece(j) > t.piece(i) {
+            assert_eq!(t.piece(j), t.piece(j-1) + 1);
+            assert_eq!(t.prev_piece(j + 1), j);
+        }
+        i = j;
+    }
+    assert_eq!(t.prev_piece(0), 0);
+}
+
+#[test]
+fn phrases_line_up_with_characters() {
+    // 'İ' lowercases to two characters.
+    let chars: Vec<char> = "İei!Ei!".chars().collect();
+    let found = find_phrases(&chars, &["ei!".to_string()]);
+    assert_eq!(found, vec![None, Some(0), Some(0), Some(0), Some(0), Some(0), Some(0)]);
+}
+
+#[test]
+fn scores_like_verify() {
+    let mut initial = State::with_size(10, 10);
+    initial.unit_sequence.push(Unit { members: vec![Cell::new(5, 5)], pivot: Cell::new(5, 5) });
+    let phrases = vec!["ll".to_string()];
+    let timeline = |letters: &str| {
+        let sol = Solution { problem_id: 0, seed: 0, tag: None, solution: letters.into() };
+        Timeline::new(&sol, &initial, &phrases)
+    };
+    let t = timeline("l\nllll");
+    assert_eq!(t.ending(), simulate::Ending::Clean);
+    assert_eq!((t.move_score(t.len()), t.power_score(t.len())), (1, 2*2*3 + 300));
+
+    // The game is over after the piece locks, so the "p" is an error
+    // and the solution scores nothing at all.
+    let t = timeline("l\nllllp");
+    assert_eq!(t.ending(), simulate::Ending::Trailing(6));
+    assert_eq!((t.move_score(6), t.power_score(6)), (1, 2*2*3 + 300));
+    assert_eq!((t.move_score(7), t.power_score(7)), (0, 0));
+    let (s, _) = simulate::replay(&string_to_commands(&t.solution.solution), &initial);
+    assert_eq!(s.score, t.move_score(t.len()));
+
+    let t = timeline("llpb");
+    assert_eq!(t.ending(), simulate::Ending::Revisit(3));
+    assert_eq!((t.move_score(4), t.power_score(4)), (0, 0));
+}
+
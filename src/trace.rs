@@ -0,0 +1,175 @@
+//! Traces of what solvers decided and why, written as JSON lines
+//! (`--trace FILE`) to be picked apart offline.
+//!
+//! Solvers record into the one global tracer, which does nothing
+//! until it is given somewhere to write.  Each record is a line, so
+//! the records of solver threads never get mixed up.
+
+use super::*;
+
+use serde::Serialize;
+use serde_json;
+use std::fs::File;
+use std::io::{self, LineWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+pub struct Tracer {
+    out: Mutex<Option<Box<dyn Write + Send>>>,
+}
+
+static GLOBAL: Tracer = Tracer::new();
+
+/// The tracer shared by every solver thread.
+pub fn global() -> &'static Tracer {
+    &GLOBAL
+}
+
+impl Default for Tracer {
+    fn default() -> Tracer {
+        Tracer::new()
+    }
+}
+
+impl Tracer {
+    /// A tracer that drops everything.
+    pub const fn new() -> Tracer {
+        Tracer { out: Mutex::new(None) }
+    }
+    /// Write records to the file at `path`, replacing it.
+    pub fn open<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.write_to(Box::new(LineWriter::new(File::create(path)?)));
+        Ok(())
+    }
+    pub fn write_to(&self, out: Box<dyn Write + Send>) {
+        *self.out.lock().unwrap() = Some(out);
+    }
+    /// Stop tracing, flushing what was written.
+    pub fn close(&self) -> io::Result<()> {
+        match self.out.lock().unwrap().take() {
+            Some(mut out) => out.flush(),
+            None => Ok(()),
+        }
+    }
+    /// Whether anything recorded goes anywhere, so solvers need not
+    /// put together records nobody will see.
+    pub fn enabled(&self) -> bool {
+        self.out.lock().unwrap().is_some()
+    }
+    /// Write `record` as a line.  If we can't, we say so once and
+    /// stop tracing rather than stop solving.
+    pub fn record<T: Serialize>(&self, record: &T) {
+        let mut out = self.out.lock().unwrap();
+        let failed = match *out {
+            Some(ref mut w) => {
+                let line = serde_json::to_string(record).expect("Couldn't turn a trace into JSON.");
+                writeln!(w, "{}", line).err()
+            },
+            None => None,
+        };
+        if let Some(e) = failed {
+            println!("Couldn't write the trace, so no more of it: {}", e);
+            *out = None;
+        }
+    }
+}
+
+/// How a search for a path to a placement went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Search {
+    Found,
+    /// Every path was tried.
+    NoPath,
+    /// We ran out of time first.
+    TimedOut,
+}
+
+/// A place the piece could have locked, and what became of it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Candidate {
+    pub pivot: Cell,
+    pub members: Vec<Cell>,
+    /// The search for any path there, if we got around to one.
+    pub search: Option<Search>,
+    /// The search for a path there using phrases of power, if there
+    /// was any path to improve on.
+    #[serde(rename = "phraseSearch")]
+    pub phrase_search: Option<Search>,
+    /// The letters that take the piece there.
+    pub path: Option<String>,
+    pub seconds: f64,
+}
+
+impl Candidate {
+    /// `u`, before we search for a path to it.
+    pub fn new(u: &Unit) -> Candidate {
+        Candidate {
+            pivot: u.pivot,
+            members: u.members.clone(),
+            search: None,
+            phrase_search: None,
+            path: None,
+            seconds: 0.0,
+        }
+    }
+}
+
+/// How one piece was placed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Piece {
+    pub solver: String,
+    #[serde(rename = "problemId")]
+    pub problem_id: i32,
+    pub seed: i32,
+    /// How many pieces were locked before this one.
+    pub piece: usize,
+    /// Every resting position, in the order they were considered.
+    pub candidates: Vec<Candidate>,
+    /// Which of `candidates` was played, if any.
+    pub chosen: Option<usize>,
+    pub seconds: f64,
+    /// The move score once the piece is locked.
+    pub score: Score,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn records_are_lines() {
+        let t = Tracer::new();
+        assert!(!t.enabled());
+        t.record(&Search::Found);
+        let buf = Shared(Arc::new(Mutex::new(Vec::new())));
+        t.write_to(Box::new(buf.clone()));
+        assert!(t.enabled());
+        let u = Unit { members: vec![Cell::new(1, 2)], pivot: Cell::new(1, 2) };
+        let mut c = Candidate::new(&u);
+        c.search = Some(Search::TimedOut);
+        t.record(&c);
+        t.record(&Search::NoPath);
+        t.close().unwrap();
+        t.record(&Search::Found);
+        let text = String::from_utf8(buf.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(serde_json::from_str::<Candidate>(lines[0]).unwrap(), c);
+        assert!(lines[0].contains("\"search\":\"timed_out\""));
+        assert_eq!(lines[1], "\"no_path\"");
+    }
+}
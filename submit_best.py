@@ -4,8 +4,27 @@ import shlex
 import re
 import json
 import glob
-
-API_TOKEN = 'FtpwGAy9ndcLXLUlH7i96rgXLgi2SzEdym2caXEsNUI='
+import os
+import stat
+import sys
+
+def api_token():
+    # Same rules as the Rust credentials module: DAVAR_API_TOKEN, or
+    # else a file only we can read.
+    token = os.environ.get('DAVAR_API_TOKEN', '').strip()
+    if token:
+        return token
+    fname = os.environ.get('DAVAR_CREDENTIALS', os.path.expanduser('~/.davar-token'))
+    if not os.path.exists(fname):
+        sys.exit("no API token: set DAVAR_API_TOKEN or put the token in " + fname + " (chmod 600)")
+    if os.stat(fname).st_mode & (stat.S_IRWXG | stat.S_IRWXO):
+        sys.exit(fname + " must not be readable by others (chmod 600)")
+    token = open(fname).read().strip()
+    if not token:
+        sys.exit(fname + " does not contain an API token")
+    return token
+
+API_TOKEN = api_token()
 TEAM_ID = '97'
 
 out_files = glob.glob("solutions/*.json")
@@ -42,10 +61,13 @@ f.close()
 
 fname = 'output.json'
 
-post_cmd = "curl --user :" + str(API_TOKEN) + " -X POST -H \"Content-Type: application/json\" " + \
+# curl reads the token from its config on stdin, since anyone on the
+# machine can read a command line with ps.
+post_cmd = "curl --config - -X POST -H \"Content-Type: application/json\" " + \
            "-d @" + fname + " https://davar.icfpcontest.org/teams/" + str(TEAM_ID) + "/solutions"
 print post_cmd
 
 split_cmd = shlex.split(post_cmd)
 
-subprocess.Popen(split_cmd)
+config = 'user = ":' + API_TOKEN.replace('\\', '\\\\').replace('"', '\\"') + '"\n'
+subprocess.Popen(split_cmd, stdin=subprocess.PIPE).communicate(config)